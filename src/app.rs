@@ -3,9 +3,12 @@ use crate::{
     get_log_spec,
     menu::{menu_wrapper, Menu, MenuType},
     modules::{
-        clock::Clock, launcher, privacy::Privacy, settings::Settings, system_info::SystemInfo,
-        title::Title, updates::Updates, workspaces::Workspaces,
+        clock::Clock, disks::Disks, displays::Displays, dock::Dock, keybinds::Keybinds,
+        launcher::Launcher, network_speed::NetworkSpeed, privacy::Privacy, settings::Settings,
+        submap::Submap, system_info::SystemInfo, taskbar::Taskbar, title::Title, updates::Updates,
+        workspaces::Workspaces,
     },
+    osd::{Osd, OsdLevel},
     style::ashell_theme,
     HEIGHT,
 };
@@ -22,12 +25,22 @@ pub struct App {
     //logger: LoggerHandle,
     config: Config,
     menu: Menu,
+    osd: Osd,
+    osd_levels: Vec<OsdLevel>,
+    launcher: Launcher,
     updates: Updates,
     workspaces: Workspaces,
     window_title: Title,
+    taskbar: Taskbar,
+    dock: Dock,
+    submap: Submap,
     system_info: SystemInfo,
+    disks: Disks,
+    network_speed: NetworkSpeed,
     clock: Clock,
     privacy: Privacy,
+    keybinds: Keybinds,
+    displays: Displays,
     pub settings: Settings,
 }
 
@@ -36,13 +49,21 @@ pub enum Message {
     None,
     ConfigChanged(Box<Config>),
     CloseMenu,
-    OpenLauncher,
+    OsdTimeout(u64),
+    Launcher(crate::modules::launcher::Message),
     Updates(crate::modules::updates::Message),
     Workspaces(crate::modules::workspaces::Message),
     Title(crate::modules::title::Message),
+    Taskbar(crate::modules::taskbar::TaskbarMessage),
+    Dock(crate::modules::dock::Message),
+    Submap(crate::modules::submap::Message),
     SystemInfo(crate::modules::system_info::Message),
+    Disks(crate::modules::disks::Message),
+    NetworkSpeed(crate::modules::network_speed::Message),
     Clock(crate::modules::clock::Message),
     Privacy(crate::modules::privacy::PrivacyMessage),
+    Keybinds(crate::modules::keybinds::Message),
+    Displays(crate::modules::displays::Message),
     Settings(crate::modules::settings::Message),
 }
 
@@ -58,12 +79,22 @@ impl Application for App {
                 //logger,
                 config,
                 menu: Menu::init(),
+                osd: Osd::init(),
+                osd_levels: Vec::new(),
+                launcher: Launcher::new(),
                 updates: Updates::new(),
                 workspaces: Workspaces::new(),
                 window_title: Title::new(),
+                taskbar: Taskbar::new(),
+                dock: Dock::new(),
+                submap: Submap::new(),
                 system_info: SystemInfo::new(),
+                disks: Disks::new(),
+                network_speed: NetworkSpeed::new(),
                 clock: Clock::new(),
                 privacy: Privacy::new(),
+                keybinds: Keybinds::new(),
+                displays: Displays::new(),
                 settings: Settings::new(),
             },
             iced::Command::none(),
@@ -92,7 +123,7 @@ impl Application for App {
     }
 
     fn update(&mut self, message: Self::Message) -> iced::Command<Self::Message> {
-        match message {
+        let command = match message {
             Message::None => iced::Command::none(),
             Message::ConfigChanged(config) => {
                 log::info!("New config: {:?}", config);
@@ -102,6 +133,7 @@ impl Application for App {
                 iced::Command::none()
             }
             Message::CloseMenu => self.menu.close(),
+            Message::OsdTimeout(generation) => self.osd.hide(generation),
             Message::Updates(message) => {
                 if let Some(updates_config) = self.config.updates.as_ref() {
                     self.updates
@@ -111,26 +143,53 @@ impl Application for App {
                     iced::Command::none()
                 }
             }
-            Message::OpenLauncher => {
-                if let Some(app_launcher_cmd) = self.config.app_launcher_cmd.as_ref() {
-                    crate::utils::launcher::execute_command(app_launcher_cmd.to_string());
-                }
-                iced::Command::none()
-            }
+            Message::Launcher(message) => self
+                .launcher
+                .update(
+                    message,
+                    &self.config.launcher,
+                    self.config.app_launcher_cmd.as_ref(),
+                    &mut self.menu,
+                )
+                .map(Message::Launcher),
             Message::Workspaces(msg) => {
                 self.workspaces.update(msg);
 
                 iced::Command::none()
             }
-            Message::Title(message) => {
-                self.window_title
-                    .update(message, self.config.truncate_title_after_length);
-                iced::Command::none()
-            }
-            Message::SystemInfo(message) => {
-                self.system_info.update(message);
+            Message::Title(message) => self
+                .window_title
+                .update(message, &mut self.menu)
+                .map(Message::Title),
+            Message::Taskbar(message) => self
+                .taskbar
+                .update(message, &mut self.menu)
+                .map(Message::Taskbar),
+            Message::Dock(message) => self
+                .dock
+                .update(
+                    message,
+                    &self.config.dock,
+                    &self.config.launcher.terminal_cmd,
+                    &mut self.menu,
+                )
+                .map(Message::Dock),
+            Message::Submap(message) => {
+                self.submap.update(message);
                 iced::Command::none()
             }
+            Message::SystemInfo(message) => self
+                .system_info
+                .update(message, &self.config.system, &mut self.menu)
+                .map(Message::SystemInfo),
+            Message::Disks(message) => self
+                .disks
+                .update(message, &mut self.menu)
+                .map(Message::Disks),
+            Message::NetworkSpeed(message) => self
+                .network_speed
+                .update(message, &mut self.menu)
+                .map(Message::NetworkSpeed),
             Message::Clock(message) => {
                 self.clock.update(message);
                 iced::Command::none()
@@ -139,15 +198,62 @@ impl Application for App {
                 .privacy
                 .update(message, &mut self.menu)
                 .map(Message::Privacy),
-            Message::Settings(message) => self
-                .settings
-                .update(message, &self.config.settings, &mut self.menu)
-                .map(Message::Settings),
+            Message::Keybinds(message) => self
+                .keybinds
+                .update(message, &mut self.menu)
+                .map(Message::Keybinds),
+            Message::Displays(message) => self
+                .displays
+                .update(message, &self.config.displays, &mut self.menu)
+                .map(Message::Displays),
+            Message::Settings(message) => {
+                let command = self
+                    .settings
+                    .update(message, &self.config.settings, &mut self.menu)
+                    .map(Message::Settings);
+
+                let osd_levels = self.settings.osd_levels();
+                // the settings menu already shows the levels
+                let changed = if self.menu.get_menu_type() == Some(MenuType::Settings) {
+                    None
+                } else {
+                    osd_levels
+                        .iter()
+                        .find(|level| {
+                            // switching the default device is not a level change
+                            self.osd_levels.iter().any(|previous| {
+                                previous.kind == level.kind
+                                    && previous.device == level.device
+                                    && previous != *level
+                            })
+                        })
+                        .cloned()
+                };
+                self.osd_levels = osd_levels;
+
+                if let Some(level) = changed {
+                    iced::Command::batch(vec![
+                        command,
+                        self.osd.show(level, &self.config.osd, Message::OsdTimeout),
+                    ])
+                } else {
+                    command
+                }
+            }
+        };
+
+        // the settings menu could be closed by a click outside or replaced by another menu
+        if self.menu.get_menu_type() != Some(MenuType::Settings) {
+            self.settings.close_dialogs();
         }
+
+        command
     }
 
     fn view(&self, id: Id) -> iced::Element<'_, Self::Message> {
-        if Some(id) == self.menu.get_id() {
+        if Some(id) == self.osd.get_id() {
+            self.osd.view()
+        } else if Some(id) == self.menu.get_id() {
             if let Some(menu_type) = self.menu.get_menu_type() {
                 menu_wrapper(
                     match menu_type {
@@ -157,11 +263,46 @@ impl Application for App {
                             .settings
                             .menu_view(&self.config.settings)
                             .map(Message::Settings),
+                        MenuType::Title => self.window_title.menu_view().map(Message::Title),
+                        MenuType::Taskbar => self.taskbar.menu_view().map(Message::Taskbar),
+                        MenuType::Keybinds => self
+                            .keybinds
+                            .menu_view(&self.config.keybinds)
+                            .map(Message::Keybinds),
+                        MenuType::Displays => self
+                            .displays
+                            .menu_view(&self.config.displays)
+                            .map(Message::Displays),
+                        MenuType::SystemInfo => self
+                            .system_info
+                            .menu_view(&self.config.system)
+                            .map(Message::SystemInfo),
+                        MenuType::Disks => {
+                            self.disks.menu_view(&self.config.disks).map(Message::Disks)
+                        }
+                        MenuType::NetworkSpeed => self
+                            .network_speed
+                            .menu_view(&self.config.network_speed)
+                            .map(Message::NetworkSpeed),
+                        MenuType::Launcher => self
+                            .launcher
+                            .menu_view(&self.config.launcher)
+                            .map(Message::Launcher),
+                        MenuType::Dock => self.dock.menu_view().map(Message::Dock),
                     },
                     match menu_type {
                         MenuType::Updates => crate::menu::MenuPosition::Left,
                         MenuType::Privacy => crate::menu::MenuPosition::Right,
                         MenuType::Settings => crate::menu::MenuPosition::Left,
+                        MenuType::Title => crate::menu::MenuPosition::Left,
+                        MenuType::Taskbar => crate::menu::MenuPosition::Left,
+                        MenuType::Keybinds => crate::menu::MenuPosition::Left,
+                        MenuType::Displays => crate::menu::MenuPosition::Left,
+                        MenuType::SystemInfo => crate::menu::MenuPosition::Left,
+                        MenuType::Disks => crate::menu::MenuPosition::Left,
+                        MenuType::NetworkSpeed => crate::menu::MenuPosition::Left,
+                        MenuType::Launcher => crate::menu::MenuPosition::Left,
+                        MenuType::Dock => crate::menu::MenuPosition::Left,
                     },
                 )
             } else {
@@ -169,21 +310,48 @@ impl Application for App {
             }
         } else {
             let mut left = column!().spacing(4).padding([8, 8, 8, 12]);
+            if !self.config.launcher.disabled || self.config.app_launcher_cmd.is_some() {
+                left = left.push(self.launcher.view().map(Message::Launcher));
+            }
+            if let Some(dock) = self.dock.view(&self.config.dock) {
+                left = left.push(dock.map(Message::Dock));
+            }
             if let Some(sysinfo) = self.system_info.view(&self.config.system) {
                 left = left.push(sysinfo.map(Message::SystemInfo));
             }
+            if let Some(disks) = self.disks.view(&self.config.disks) {
+                left = left.push(disks.map(Message::Disks));
+            }
+            if let Some(network_speed) = self.network_speed.view(&self.config.network_speed) {
+                left = left.push(network_speed.map(Message::NetworkSpeed));
+            }
+            if let Some(taskbar) = self.taskbar.view(&self.config.taskbar) {
+                left = left.push(taskbar.map(Message::Taskbar));
+            }
 
             let mut center = column!().spacing(4).padding(8);
             center = center.push(
                 self.workspaces
-                    .view(&self.config.appearance.workspace_colors)
+                    .view(
+                        &self.config.workspaces,
+                        &self.config.appearance.workspace_colors,
+                    )
                     .map(Message::Workspaces),
             );
+            if let Some(title) = self
+                .window_title
+                .view(&self.config.title, self.config.truncate_title_after_length)
+            {
+                center = center.push(title.map(Message::Title));
+            }
 
             let right = Column::with_children(
                 vec![Some(
                     Column::with_children(
                         vec![
+                            self.submap
+                                .view(&self.config.submap)
+                                .map(|submap| submap.map(Message::Submap)),
                             Some(
                                 self.clock
                                     .view(&self.config.clock.format)
@@ -194,6 +362,16 @@ impl Application for App {
                             } else {
                                 Some(self.privacy.view().map(Message::Privacy))
                             },
+                            if self.config.keybinds.disabled {
+                                None
+                            } else {
+                                Some(self.keybinds.view().map(Message::Keybinds))
+                            },
+                            if self.config.displays.disabled {
+                                None
+                            } else {
+                                Some(self.displays.view().map(Message::Displays))
+                            },
                             Some(self.settings.view().map(Message::Settings)),
                         ]
                         .into_iter()
@@ -244,10 +422,60 @@ impl Application for App {
                 }),
                 Some(self.workspaces.subscription().map(Message::Workspaces)),
                 Some(self.window_title.subscription().map(Message::Title)),
+                if self.config.taskbar.disabled {
+                    None
+                } else {
+                    Some(self.taskbar.subscription().map(Message::Taskbar))
+                },
+                if self.config.dock.disabled {
+                    None
+                } else {
+                    Some(self.dock.subscription().map(Message::Dock))
+                },
+                if self.config.submap.disabled {
+                    None
+                } else {
+                    Some(self.submap.subscription().map(Message::Submap))
+                },
+                if self.config.displays.disabled {
+                    None
+                } else {
+                    Some(self.displays.subscription().map(Message::Displays))
+                },
+                if self.config.launcher.disabled {
+                    None
+                } else {
+                    Some(self.launcher.subscription().map(Message::Launcher))
+                },
                 Some(self.system_info.subscription().map(Message::SystemInfo)),
+                if self.config.disks.disabled {
+                    None
+                } else {
+                    Some(
+                        self.disks
+                            .subscription(&self.config.disks)
+                            .map(Message::Disks),
+                    )
+                },
+                if self.config.network_speed.disabled {
+                    None
+                } else {
+                    Some(
+                        self.network_speed
+                            .subscription(&self.config.network_speed)
+                            .map(Message::NetworkSpeed),
+                    )
+                },
                 Some(self.clock.subscription().map(Message::Clock)),
                 Some(self.privacy.subscription().map(Message::Privacy)),
-                Some(self.settings.subscription().map(Message::Settings)),
+                Some(
+                    self.settings
+                        .subscription(
+                            &self.config.settings,
+                            self.menu.get_menu_type() == Some(MenuType::Settings),
+                        )
+                        .map(Message::Settings),
+                ),
                 Some(config::subscription()),
             ]
             .into_iter()
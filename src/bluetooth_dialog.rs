@@ -0,0 +1,100 @@
+use crate::modules::settings::bluetooth::{AgentRequest, AgentRequestKind};
+use iced::{
+    widget::{button, container, horizontal_space, row, text, text_input, Column},
+    Element,
+};
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    InputChanged(String),
+    DialogConfirmed,
+    DialogCancelled,
+    DialogDismissed,
+}
+
+pub fn view<'a>(request: &AgentRequest, current_input: &str) -> Element<'a, Message> {
+    let (title, description, code, confirm) = match &request.kind {
+        AgentRequestKind::PinCode => (
+            "Bluetooth pairing",
+            format!("Enter the PIN code to pair {}", request.device),
+            None,
+            Some("Pair"),
+        ),
+        AgentRequestKind::Passkey => (
+            "Bluetooth pairing",
+            format!("Enter the passkey displayed on {}", request.device),
+            None,
+            Some("Pair"),
+        ),
+        AgentRequestKind::DisplayPinCode(pincode) => (
+            "Bluetooth pairing",
+            format!("Type this PIN code on {}", request.device),
+            Some(pincode.to_string()),
+            None,
+        ),
+        AgentRequestKind::DisplayPasskey(passkey) => (
+            "Bluetooth pairing",
+            format!("Type this passkey on {}", request.device),
+            Some(format!("{:06}", passkey)),
+            None,
+        ),
+        AgentRequestKind::Confirmation(passkey) => (
+            "Bluetooth pairing",
+            format!("Confirm that {} shows the same passkey", request.device),
+            Some(format!("{:06}", passkey)),
+            Some("Confirm"),
+        ),
+        AgentRequestKind::Authorization => (
+            "Bluetooth pairing",
+            format!("Allow {} to pair with this device?", request.device),
+            None,
+            Some("Allow"),
+        ),
+        AgentRequestKind::AuthorizeService(uuid) => (
+            "Bluetooth authorization",
+            format!("Allow {} to use the service {}?", request.device, uuid),
+            None,
+            Some("Allow"),
+        ),
+    };
+
+    container(
+        Column::with_children(
+            vec![
+                Some(text(title).size(22).into()),
+                Some(text(description).into()),
+                code.map(|code| text(code).size(32).into()),
+                request.kind.needs_input().then(|| {
+                    text_input("", current_input)
+                        .on_input(Message::InputChanged)
+                        .on_submit(Message::DialogConfirmed)
+                        .into()
+                }),
+                Some(
+                    row!(
+                        horizontal_space(iced::Length::Fill),
+                        if let Some(confirm) = confirm {
+                            row!(
+                                button("Reject").on_press(Message::DialogCancelled),
+                                button(confirm).on_press(Message::DialogConfirmed)
+                            )
+                            .spacing(8)
+                        } else {
+                            row!(button("Close").on_press(Message::DialogDismissed))
+                        }
+                    )
+                    .width(iced::Length::Fill)
+                    .height(iced::Length::Fixed(32.))
+                    .into(),
+                ),
+            ]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>(),
+        )
+        .spacing(16)
+        .padding(16)
+        .max_width(350.),
+    )
+    .into()
+}
@@ -1,6 +1,6 @@
 use iced::widget::{text, Text};
 
-#[derive(Copy, Clone, Default)]
+#[derive(Copy, Clone, Default, PartialEq, Eq)]
 pub enum Icons {
     #[default]
     None,
@@ -13,6 +13,13 @@ pub enum Icons {
     Cpu,
     Mem,
     Temp,
+    Swap,
+    Load,
+    Uptime,
+    CpuFrequency,
+    Disk,
+    Download,
+    Upload,
     Speaker0,
     Speaker1,
     Speaker2,
@@ -23,6 +30,7 @@ pub enum Icons {
     Mic0,
     Mic1,
     MonitorSpeaker,
+    Mixer,
     ScreenShare,
     Battery0,
     Battery1,
@@ -57,6 +65,24 @@ pub enum Icons {
     Point,
     Close,
     VerticalDots,
+    Keyboard,
+    Display,
+    Rotate,
+    Flip,
+    WindowFloating,
+    WindowPin,
+    WindowFullscreen,
+    WindowMaximized,
+    AppGeneric,
+    AppTerminal,
+    AppBrowser,
+    AppChromium,
+    AppCode,
+    AppFiles,
+    AppMusic,
+    AppChat,
+    AppMail,
+    AppSteam,
 }
 
 impl From<Icons> for &'static str {
@@ -72,6 +98,13 @@ impl From<Icons> for &'static str {
             Icons::Cpu => "󰔂",
             Icons::Mem => "󰘚",
             Icons::Temp => "󰔏",
+            Icons::Swap => "󰾵",
+            Icons::Load => "󰊚",
+            Icons::Uptime => "󰔟",
+            Icons::CpuFrequency => "󰓅",
+            Icons::Disk => "󰋊",
+            Icons::Download => "󰇚",
+            Icons::Upload => "󰕒",
             Icons::Speaker0 => "󰸈",
             Icons::Speaker1 => "󰕿",
             Icons::Speaker2 => "󰖀",
@@ -83,6 +116,7 @@ impl From<Icons> for &'static str {
             Icons::Mic1 => "󰍬",
             Icons::ScreenShare => "󱒃",
             Icons::MonitorSpeaker => "󰽟",
+            Icons::Mixer => "󰘮",
             Icons::Battery0 => "󰂃",
             Icons::Battery1 => "󰁼",
             Icons::Battery2 => "󰁾",
@@ -116,6 +150,24 @@ impl From<Icons> for &'static str {
             Icons::Point => "",
             Icons::Close => "󰅖",
             Icons::VerticalDots => "󰇙",
+            Icons::Keyboard => "󰌌",
+            Icons::Display => "󰍹",
+            Icons::Rotate => "󰑥",
+            Icons::Flip => "󰯌",
+            Icons::WindowFloating => "󰖲",
+            Icons::WindowPin => "󰐃",
+            Icons::WindowFullscreen => "󰊓",
+            Icons::WindowMaximized => "󰁌",
+            Icons::AppGeneric => "󰣆",
+            Icons::AppTerminal => "",
+            Icons::AppBrowser => "󰈹",
+            Icons::AppChromium => "",
+            Icons::AppCode => "󰨞",
+            Icons::AppFiles => "󰉋",
+            Icons::AppMusic => "󰓇",
+            Icons::AppChat => "󰙯",
+            Icons::AppMail => "󰇮",
+            Icons::AppSteam => "󰓓",
         }
     }
 }
@@ -123,3 +175,60 @@ impl From<Icons> for &'static str {
 pub fn icon<'a>(r#type: Icons) -> Text<'a> {
     text(std::convert::Into::<&'static str>::into(r#type))
 }
+
+/// A window class matches a name when one of its reverse-DNS parts is the name itself
+/// or starts with it followed by a separator, e.g. `org.gnome.Nautilus` or `code-oss`
+fn class_matches(class: &str, names: &[&str]) -> bool {
+    class.split('.').any(|part| {
+        names.iter().any(|name| {
+            part.strip_prefix(name)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with(['-', '_']))
+        })
+    })
+}
+
+pub fn app_icon(class: &str) -> Icons {
+    let class = class.to_lowercase();
+
+    if class_matches(
+        &class,
+        &[
+            "kitty",
+            "alacritty",
+            "foot",
+            "wezterm",
+            "ghostty",
+            "terminal",
+            "gnome-terminal",
+            "konsole",
+        ],
+    ) {
+        Icons::AppTerminal
+    } else if class_matches(&class, &["firefox", "librewolf", "zen"]) {
+        Icons::AppBrowser
+    } else if class_matches(&class, &["chromium", "chrome", "google-chrome", "brave"]) {
+        Icons::AppChromium
+    } else if class_matches(&class, &["code", "codium", "vscodium", "zed", "neovide"]) {
+        Icons::AppCode
+    } else if class_matches(
+        &class,
+        &["nautilus", "thunar", "dolphin", "nemo", "pcmanfm"],
+    ) {
+        Icons::AppFiles
+    } else if class_matches(&class, &["spotify", "music", "rhythmbox"]) {
+        Icons::AppMusic
+    } else if class_matches(
+        &class,
+        &[
+            "discord", "vesktop", "telegram", "slack", "signal", "element",
+        ],
+    ) {
+        Icons::AppChat
+    } else if class_matches(&class, &["thunderbird", "evolution", "geary"]) {
+        Icons::AppMail
+    } else if class_matches(&class, &["steam"]) {
+        Icons::AppSteam
+    } else {
+        Icons::AppGeneric
+    }
+}
@@ -1 +1,3 @@
 pub mod icons;
+pub mod scroll_area;
+pub mod sparkline;
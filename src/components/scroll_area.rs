@@ -0,0 +1,224 @@
+//! Emit messages on mouse wheel events.
+use iced::advanced::layout::{self, Layout};
+use iced::advanced::overlay;
+use iced::advanced::renderer;
+use iced::advanced::widget::tree::{self, Tree};
+use iced::advanced::widget::{Operation, OperationOutputWrapper};
+use iced::advanced::{mouse, Clipboard, Shell, Widget};
+use iced::{event, Element, Event, Length, Rectangle, Size};
+
+/// Scrolled pixels needed for a step, touchpads report small and frequent pixel deltas.
+const PIXELS_PER_STEP: f32 = 20.;
+
+/// A container that emits a message when the mouse wheel is scrolled over its content.
+///
+/// The message receives the direction of the scroll: `1` up, `-1` down.
+/// A message is emitted for each scrolled line or every [`PIXELS_PER_STEP`] pixels.
+#[allow(missing_debug_implementations)]
+pub struct ScrollArea<'a, Message, Theme = iced::Theme, Renderer = iced::Renderer> {
+    content: Element<'a, Message, Theme, Renderer>,
+    on_scroll: Box<dyn Fn(i32) -> Message + 'a>,
+}
+
+impl<'a, Message, Theme, Renderer> ScrollArea<'a, Message, Theme, Renderer> {
+    /// Creates a [`ScrollArea`] with the given content.
+    pub fn new(
+        content: impl Into<Element<'a, Message, Theme, Renderer>>,
+        on_scroll: impl Fn(i32) -> Message + 'a,
+    ) -> Self {
+        ScrollArea {
+            content: content.into(),
+            on_scroll: Box::new(on_scroll),
+        }
+    }
+}
+
+/// The local state of a [`ScrollArea`].
+#[derive(Debug, Default)]
+struct State {
+    /// Scrolled pixels not turned into a step yet
+    pixels: f32,
+}
+
+impl<'a, Message, Theme, Renderer> Widget<Message, Theme, Renderer>
+    for ScrollArea<'a, Message, Theme, Renderer>
+where
+    Renderer: iced::advanced::Renderer,
+{
+    fn tag(&self) -> tree::Tag {
+        tree::Tag::of::<State>()
+    }
+
+    fn state(&self) -> tree::State {
+        tree::State::new(State::default())
+    }
+
+    fn children(&self) -> Vec<Tree> {
+        vec![Tree::new(&self.content)]
+    }
+
+    fn diff(&mut self, tree: &mut Tree) {
+        tree.diff_children(std::slice::from_mut(&mut self.content))
+    }
+
+    fn size(&self) -> Size<Length> {
+        self.content.as_widget().size()
+    }
+
+    fn layout(
+        &self,
+        tree: &mut Tree,
+        renderer: &Renderer,
+        limits: &layout::Limits,
+    ) -> layout::Node {
+        self.content
+            .as_widget()
+            .layout(&mut tree.children[0], renderer, limits)
+    }
+
+    fn operate(
+        &self,
+        tree: &mut Tree,
+        layout: Layout<'_>,
+        renderer: &Renderer,
+        operation: &mut dyn Operation<OperationOutputWrapper<Message>>,
+    ) {
+        self.content
+            .as_widget()
+            .operate(&mut tree.children[0], layout, renderer, operation);
+    }
+
+    fn on_event(
+        &mut self,
+        tree: &mut Tree,
+        event: Event,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        renderer: &Renderer,
+        clipboard: &mut dyn Clipboard,
+        shell: &mut Shell<'_, Message>,
+        viewport: &Rectangle,
+    ) -> event::Status {
+        if let event::Status::Captured = self.content.as_widget_mut().on_event(
+            &mut tree.children[0],
+            event.clone(),
+            layout,
+            cursor,
+            renderer,
+            clipboard,
+            shell,
+            viewport,
+        ) {
+            return event::Status::Captured;
+        }
+
+        if let Event::Mouse(mouse::Event::WheelScrolled { delta }) = event {
+            if cursor.is_over(layout.bounds()) {
+                let state = tree.state.downcast_mut::<State>();
+
+                let steps = match delta {
+                    mouse::ScrollDelta::Lines { y, .. } => {
+                        state.pixels = 0.;
+
+                        if y == 0. {
+                            0
+                        } else {
+                            y.abs().round().max(1.) as i32 * y.signum() as i32
+                        }
+                    }
+                    mouse::ScrollDelta::Pixels { y, .. } => {
+                        // start over when the direction changes
+                        if state.pixels * y < 0. {
+                            state.pixels = 0.;
+                        }
+                        state.pixels += y;
+
+                        let steps = (state.pixels / PIXELS_PER_STEP).trunc();
+                        state.pixels -= steps * PIXELS_PER_STEP;
+
+                        steps as i32
+                    }
+                };
+
+                for _ in 0..steps.abs() {
+                    shell.publish((self.on_scroll)(steps.signum()));
+                }
+
+                if steps != 0 {
+                    return event::Status::Captured;
+                }
+            }
+        }
+
+        event::Status::Ignored
+    }
+
+    fn mouse_interaction(
+        &self,
+        tree: &Tree,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        viewport: &Rectangle,
+        renderer: &Renderer,
+    ) -> mouse::Interaction {
+        self.content.as_widget().mouse_interaction(
+            &tree.children[0],
+            layout,
+            cursor,
+            viewport,
+            renderer,
+        )
+    }
+
+    fn draw(
+        &self,
+        tree: &Tree,
+        renderer: &mut Renderer,
+        theme: &Theme,
+        style: &renderer::Style,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        viewport: &Rectangle,
+    ) {
+        self.content.as_widget().draw(
+            &tree.children[0],
+            renderer,
+            theme,
+            style,
+            layout,
+            cursor,
+            viewport,
+        );
+    }
+
+    fn overlay<'b>(
+        &'b mut self,
+        tree: &'b mut Tree,
+        layout: Layout<'_>,
+        renderer: &Renderer,
+    ) -> Option<overlay::Element<'b, Message, Theme, Renderer>> {
+        self.content
+            .as_widget_mut()
+            .overlay(&mut tree.children[0], layout, renderer)
+    }
+}
+
+impl<'a, Message, Theme, Renderer> From<ScrollArea<'a, Message, Theme, Renderer>>
+    for Element<'a, Message, Theme, Renderer>
+where
+    Message: 'a,
+    Theme: 'a,
+    Renderer: iced::advanced::Renderer + 'a,
+{
+    fn from(scroll_area: ScrollArea<'a, Message, Theme, Renderer>) -> Self {
+        Self::new(scroll_area)
+    }
+}
+
+/// Creates a [`ScrollArea`] with the given content.
+pub fn scroll_area<'a, Message, Theme, Renderer>(
+    content: impl Into<Element<'a, Message, Theme, Renderer>>,
+    on_scroll: impl Fn(i32) -> Message + 'a,
+) -> ScrollArea<'a, Message, Theme, Renderer> {
+    ScrollArea::new(content, on_scroll)
+}
@@ -0,0 +1,39 @@
+use iced::{
+    widget::{container, Row, Space},
+    Alignment, Element, Length, Theme,
+};
+use std::collections::VecDeque;
+
+const GRAPH_HEIGHT: f32 = 32.;
+
+/// Bar graph of the samples, scaled between 0 and `max`
+pub fn sparkline<'a, Message: 'a>(samples: &VecDeque<f32>, max: f32) -> Element<'a, Message> {
+    Row::with_children(
+        samples
+            .iter()
+            .map(|value| {
+                container(Space::new(
+                    Length::Fixed(4.),
+                    Length::Fixed((value / max).clamp(0., 1.) * GRAPH_HEIGHT + 1.),
+                ))
+                .style(|theme: &Theme| container::Appearance {
+                    background: Some(theme.palette().primary.into()),
+                    ..Default::default()
+                })
+                .into()
+            })
+            .collect::<Vec<_>>(),
+    )
+    .spacing(1)
+    .height(Length::Fixed(GRAPH_HEIGHT + 1.))
+    .align_items(Alignment::End)
+    .into()
+}
+
+/// Appends a sample dropping the oldest ones above `size`
+pub fn push_sample(samples: &mut VecDeque<f32>, value: f32, size: usize) {
+    while samples.len() >= size {
+        samples.pop_front();
+    }
+    samples.push_back(value);
+}
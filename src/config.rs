@@ -6,10 +6,11 @@ use iced::{
 };
 use inotify::{EventMask, Inotify, WatchMask};
 use log::warn;
+use regex::Regex;
 use serde::{Deserialize, Deserializer};
-use std::{env, fs::File, path::Path};
+use std::{collections::HashMap, env, fs::File, path::Path, time::Duration};
 
-use crate::app::Message;
+use crate::{app::Message, utils::notifications::Urgency};
 
 const CONFIG_PATH: &str = "~/.config/ashell.yml";
 
@@ -20,11 +21,229 @@ pub struct UpdatesModuleConfig {
     pub update_cmd: String,
 }
 
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LauncherModuleConfig {
+    #[serde(default)]
+    pub disabled: bool,
+    #[serde(default = "default_terminal_cmd")]
+    pub terminal_cmd: String,
+    #[serde(default = "default_launcher_max_results")]
+    pub max_results: usize,
+}
+
+fn default_terminal_cmd() -> String {
+    "xdg-terminal-exec".to_string()
+}
+
+fn default_launcher_max_results() -> usize {
+    10
+}
+
+impl Default for LauncherModuleConfig {
+    fn default() -> Self {
+        Self {
+            disabled: false,
+            terminal_cmd: default_terminal_cmd(),
+            max_results: default_launcher_max_results(),
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum WorkspaceVisibilityMode {
+    #[default]
+    All,
+    MonitorSpecific,
+}
+
+#[derive(Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspacesModuleConfig {
+    #[serde(default)]
+    pub visibility_mode: WorkspaceVisibilityMode,
+    #[serde(default)]
+    pub persistent_workspaces: u32,
+    #[serde(default)]
+    pub monitor_workspaces: HashMap<String, Vec<i32>>,
+    pub output: Option<String>,
+}
+
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum TitleRenderMode {
+    #[default]
+    Vertical,
+    Horizontal,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TitleModuleConfig {
+    #[serde(default)]
+    pub disabled: bool,
+    #[serde(default)]
+    pub mode: TitleRenderMode,
+    #[serde(default = "default_show_class_icon")]
+    pub show_class_icon: bool,
+    #[serde(default = "default_vertical_max_length")]
+    pub vertical_max_length: u32,
+}
+
+fn default_show_class_icon() -> bool {
+    true
+}
+
+fn default_vertical_max_length() -> u32 {
+    12
+}
+
+impl Default for TitleModuleConfig {
+    fn default() -> Self {
+        Self {
+            disabled: false,
+            mode: TitleRenderMode::default(),
+            show_class_icon: default_show_class_icon(),
+            vertical_max_length: default_vertical_max_length(),
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskbarModuleConfig {
+    #[serde(default)]
+    pub disabled: bool,
+}
+
+#[derive(Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct DockModuleConfig {
+    #[serde(default)]
+    pub disabled: bool,
+    #[serde(default)]
+    pub pinned: Vec<String>,
+}
+
+#[derive(Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SubmapConfig {
+    pub label: Option<String>,
+    pub color: Option<HexColor>,
+}
+
+#[derive(Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SubmapModuleConfig {
+    #[serde(default)]
+    pub disabled: bool,
+    #[serde(default)]
+    pub submaps: HashMap<String, SubmapConfig>,
+}
+
+#[derive(Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct KeybindsModuleConfig {
+    #[serde(default)]
+    pub disabled: bool,
+    #[serde(default)]
+    pub descriptions: HashMap<String, String>,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DisplayLayout {
+    pub name: String,
+    pub monitors: Vec<String>,
+}
+
+#[derive(Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct DisplaysModuleConfig {
+    #[serde(default)]
+    pub disabled: bool,
+    #[serde(default)]
+    pub layouts: Vec<DisplayLayout>,
+}
+
+#[derive(Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum TemperatureSensor {
+    #[default]
+    Max,
+    Label(String),
+    #[serde(deserialize_with = "deserialize_regex")]
+    Regex(Regex),
+}
+
+fn deserialize_regex<'de, D>(deserializer: D) -> Result<Regex, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let regex = String::deserialize(deserializer)?;
+
+    Regex::new(&regex).map_err(serde::de::Error::custom)
+}
+
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum TemperatureUnit {
+    #[default]
+    Celsius,
+    Fahrenheit,
+}
+
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum SystemMetric {
+    Cpu,
+    CpuCores,
+    CpuFrequency,
+    Memory,
+    Swap,
+    Temperature,
+    LoadAverage,
+    Uptime,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AlertRule {
+    pub metric: SystemMetric,
+    pub threshold: f32,
+    #[serde(default = "default_alert_duration")]
+    pub duration: u64,
+    #[serde(default = "default_alert_hysteresis")]
+    pub hysteresis: f32,
+    #[serde(default = "default_alert_notify")]
+    pub notify: bool,
+    #[serde(default)]
+    pub urgency: Urgency,
+    pub command: Option<String>,
+}
+
+fn default_alert_duration() -> u64 {
+    30
+}
+
+fn default_alert_hysteresis() -> f32 {
+    5.
+}
+
+fn default_alert_notify() -> bool {
+    true
+}
+
 #[derive(Deserialize, Clone, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct SystemModuleConfig {
     #[serde(default)]
     pub disabled: bool,
+    #[serde(default = "default_system_metrics")]
+    pub metrics: Vec<SystemMetric>,
+    #[serde(default)]
+    pub intervals: HashMap<SystemMetric, u64>,
     #[serde(default = "default_cpu_warn_threshold")]
     pub cpu_warn_threshold: u32,
     #[serde(default = "default_cpu_alert_threshold")]
@@ -37,6 +256,44 @@ pub struct SystemModuleConfig {
     pub temp_warn_threshold: i32,
     #[serde(default = "default_temp_alert_threshold")]
     pub temp_alert_threshold: i32,
+    #[serde(default = "default_swap_warn_threshold")]
+    pub swap_warn_threshold: u32,
+    #[serde(default = "default_swap_alert_threshold")]
+    pub swap_alert_threshold: u32,
+    #[serde(default = "default_load_warn_threshold")]
+    pub load_warn_threshold: u32,
+    #[serde(default = "default_load_alert_threshold")]
+    pub load_alert_threshold: u32,
+    #[serde(
+        default,
+        deserialize_with = "serde_yaml::with::singleton_map::deserialize"
+    )]
+    pub temperature_sensor: TemperatureSensor,
+    #[serde(default)]
+    pub temperature_unit: TemperatureUnit,
+    #[serde(default)]
+    pub alerts: Vec<AlertRule>,
+}
+
+fn default_system_metrics() -> Vec<SystemMetric> {
+    vec![
+        SystemMetric::Cpu,
+        SystemMetric::Memory,
+        SystemMetric::Temperature,
+    ]
+}
+
+const DEFAULT_SYSTEM_INTERVAL: u64 = 5;
+
+impl SystemModuleConfig {
+    pub fn interval(&self, metric: SystemMetric) -> Duration {
+        Duration::from_secs(
+            self.intervals
+                .get(&metric)
+                .copied()
+                .unwrap_or(DEFAULT_SYSTEM_INTERVAL),
+        )
+    }
 }
 
 fn default_cpu_warn_threshold() -> u32 {
@@ -63,16 +320,109 @@ fn default_temp_alert_threshold() -> i32 {
     80
 }
 
+fn default_swap_warn_threshold() -> u32 {
+    50
+}
+
+fn default_swap_alert_threshold() -> u32 {
+    80
+}
+
+fn default_load_warn_threshold() -> u32 {
+    70
+}
+
+fn default_load_alert_threshold() -> u32 {
+    100
+}
+
 impl Default for SystemModuleConfig {
     fn default() -> Self {
         Self {
             disabled: false,
+            metrics: default_system_metrics(),
+            intervals: HashMap::new(),
             cpu_warn_threshold: default_cpu_warn_threshold(),
             cpu_alert_threshold: default_cpu_alert_threshold(),
             mem_warn_threshold: default_mem_warn_threshold(),
             mem_alert_threshold: default_mem_alert_threshold(),
             temp_warn_threshold: default_temp_warn_threshold(),
             temp_alert_threshold: default_temp_alert_threshold(),
+            swap_warn_threshold: default_swap_warn_threshold(),
+            swap_alert_threshold: default_swap_alert_threshold(),
+            load_warn_threshold: default_load_warn_threshold(),
+            load_alert_threshold: default_load_alert_threshold(),
+            temperature_sensor: TemperatureSensor::default(),
+            temperature_unit: TemperatureUnit::default(),
+            alerts: Vec::new(),
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DisksModuleConfig {
+    #[serde(default)]
+    pub disabled: bool,
+    #[serde(default = "default_disk_mounts")]
+    pub mounts: Vec<String>,
+    #[serde(default = "default_disk_interval")]
+    pub interval: u64,
+    #[serde(default = "default_disk_usage_warn_threshold")]
+    pub usage_warn_threshold: u32,
+    #[serde(default = "default_disk_usage_alert_threshold")]
+    pub usage_alert_threshold: u32,
+}
+
+fn default_disk_mounts() -> Vec<String> {
+    vec!["/".to_string()]
+}
+
+fn default_disk_interval() -> u64 {
+    5
+}
+
+fn default_disk_usage_warn_threshold() -> u32 {
+    80
+}
+
+fn default_disk_usage_alert_threshold() -> u32 {
+    90
+}
+
+impl Default for DisksModuleConfig {
+    fn default() -> Self {
+        Self {
+            disabled: false,
+            mounts: default_disk_mounts(),
+            interval: default_disk_interval(),
+            usage_warn_threshold: default_disk_usage_warn_threshold(),
+            usage_alert_threshold: default_disk_usage_alert_threshold(),
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkSpeedModuleConfig {
+    #[serde(default)]
+    pub disabled: bool,
+    #[serde(default)]
+    pub interfaces: Vec<String>,
+    #[serde(default = "default_network_speed_interval")]
+    pub interval: u64,
+}
+
+fn default_network_speed_interval() -> u64 {
+    2
+}
+
+impl Default for NetworkSpeedModuleConfig {
+    fn default() -> Self {
+        Self {
+            disabled: false,
+            interfaces: Vec::new(),
+            interval: default_network_speed_interval(),
         }
     }
 }
@@ -91,7 +441,7 @@ impl Default for ClockModuleConfig {
     }
 }
 
-#[derive(Deserialize, Default, Clone, Debug)]
+#[derive(Deserialize, Clone, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct SettingsModuleConfig {
     pub lock_cmd: Option<String>,
@@ -100,8 +450,138 @@ pub struct SettingsModuleConfig {
     pub wifi_more_cmd: Option<String>,
     pub vpn_more_cmd: Option<String>,
     pub bluetooth_more_cmd: Option<String>,
+    #[serde(default)]
+    pub audio_backend: AudioBackend,
+    #[serde(default)]
+    pub audio_devices: AudioDevicesConfig,
+    #[serde(default = "default_max_volume")]
+    pub max_volume: u32,
+    #[serde(default = "default_volume_step")]
+    pub volume_step: u32,
+}
+
+fn default_max_volume() -> u32 {
+    100
+}
+
+fn default_volume_step() -> u32 {
+    5
+}
+
+impl Default for SettingsModuleConfig {
+    fn default() -> Self {
+        Self {
+            lock_cmd: None,
+            audio_sinks_more_cmd: None,
+            audio_sources_more_cmd: None,
+            wifi_more_cmd: None,
+            vpn_more_cmd: None,
+            bluetooth_more_cmd: None,
+            audio_backend: AudioBackend::default(),
+            audio_devices: AudioDevicesConfig::default(),
+            max_volume: default_max_volume(),
+            volume_step: default_volume_step(),
+        }
+    }
 }
 
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AudioBackend {
+    #[default]
+    PipeWire,
+    PulseAudio,
+}
+
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum AudioPortType {
+    Headphones,
+    Speaker,
+    Headset,
+    Hdmi,
+}
+
+/// Matches the sinks or sources by name or description and, optionally, by port type
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AudioDeviceRule {
+    #[serde(default, deserialize_with = "deserialize_optional_regex")]
+    pub pattern: Option<Regex>,
+    pub port_type: Option<AudioPortType>,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AudioDeviceVolume {
+    #[serde(deserialize_with = "deserialize_regex")]
+    pub pattern: Regex,
+    pub volume: u32,
+}
+
+#[derive(Deserialize, Default, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AudioDevicesConfig {
+    #[serde(default)]
+    pub sink_priority: Vec<AudioDeviceRule>,
+    #[serde(default)]
+    pub source_priority: Vec<AudioDeviceRule>,
+    #[serde(default, deserialize_with = "deserialize_regexes")]
+    pub never_select: Vec<Regex>,
+    #[serde(default)]
+    pub default_volumes: Vec<AudioDeviceVolume>,
+}
+
+fn deserialize_optional_regex<'de, D>(deserializer: D) -> Result<Option<Regex>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Option::<String>::deserialize(deserializer)?
+        .map(|regex| Regex::new(&regex).map_err(serde::de::Error::custom))
+        .transpose()
+}
+
+fn deserialize_regexes<'de, D>(deserializer: D) -> Result<Vec<Regex>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Vec::<String>::deserialize(deserializer)?
+        .iter()
+        .map(|regex| Regex::new(regex).map_err(serde::de::Error::custom))
+        .collect()
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct OsdConfig {
+    #[serde(default = "default_osd_timeout")]
+    pub timeout: u64,
+    #[serde(default = "default_osd_enabled")]
+    pub volume: bool,
+    #[serde(default = "default_osd_enabled")]
+    pub microphone: bool,
+    #[serde(default = "default_osd_enabled")]
+    pub brightness: bool,
+}
+
+fn default_osd_timeout() -> u64 {
+    1500
+}
+
+fn default_osd_enabled() -> bool {
+    true
+}
+
+impl Default for OsdConfig {
+    fn default() -> Self {
+        Self {
+            timeout: default_osd_timeout(),
+            volume: default_osd_enabled(),
+            microphone: default_osd_enabled(),
+            brightness: default_osd_enabled(),
+        }
+    }
+}
 
 #[derive(Deserialize, Clone, Debug)]
 #[serde(untagged)]
@@ -253,17 +733,39 @@ pub struct Config {
     #[serde(default = "default_log_level")]
     pub log_level: log::LevelFilter,
     pub app_launcher_cmd: Option<String>,
+    #[serde(default)]
+    pub launcher: LauncherModuleConfig,
     #[serde(default = "default_truncate_title_after_length")]
     pub truncate_title_after_length: u32,
+    #[serde(default)]
+    pub title: TitleModuleConfig,
     #[serde(deserialize_with = "try_default")]
     pub updates: Option<UpdatesModuleConfig>,
     #[serde(default)]
+    pub workspaces: WorkspacesModuleConfig,
+    #[serde(default)]
+    pub taskbar: TaskbarModuleConfig,
+    #[serde(default)]
+    pub dock: DockModuleConfig,
+    #[serde(default)]
+    pub submap: SubmapModuleConfig,
+    #[serde(default)]
+    pub keybinds: KeybindsModuleConfig,
+    #[serde(default)]
+    pub displays: DisplaysModuleConfig,
+    #[serde(default)]
     pub system: SystemModuleConfig,
     #[serde(default)]
+    pub disks: DisksModuleConfig,
+    #[serde(default)]
+    pub network_speed: NetworkSpeedModuleConfig,
+    #[serde(default)]
     pub clock: ClockModuleConfig,
     #[serde(default)]
     pub settings: SettingsModuleConfig,
     #[serde(default)]
+    pub osd: OsdConfig,
+    #[serde(default)]
     pub appearance: Appearance,
 }
 
@@ -295,11 +797,22 @@ impl Default for Config {
         Self {
             log_level: default_log_level(),
             app_launcher_cmd: None,
+            launcher: LauncherModuleConfig::default(),
             truncate_title_after_length: default_truncate_title_after_length(),
+            title: TitleModuleConfig::default(),
             updates: None,
+            workspaces: WorkspacesModuleConfig::default(),
+            taskbar: TaskbarModuleConfig::default(),
+            dock: DockModuleConfig::default(),
+            submap: SubmapModuleConfig::default(),
+            keybinds: KeybindsModuleConfig::default(),
+            displays: DisplaysModuleConfig::default(),
             system: SystemModuleConfig::default(),
+            disks: DisksModuleConfig::default(),
+            network_speed: NetworkSpeedModuleConfig::default(),
             clock: ClockModuleConfig::default(),
             settings: SettingsModuleConfig::default(),
+            osd: OsdConfig::default(),
             appearance: Appearance::default(),
         }
     }
@@ -16,11 +16,13 @@ use log::{error, LevelFilter};
 use std::panic;
 
 mod app;
+mod bluetooth_dialog;
 mod centerbox;
 mod components;
 mod config;
 mod menu;
 mod modules;
+mod osd;
 mod password_dialog;
 mod style;
 mod utils;
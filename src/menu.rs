@@ -63,6 +63,15 @@ pub enum MenuType {
     Updates,
     Privacy,
     Settings,
+    Title,
+    Taskbar,
+    Keybinds,
+    Displays,
+    SystemInfo,
+    Disks,
+    NetworkSpeed,
+    Launcher,
+    Dock,
 }
 
 pub struct Menu {
@@ -89,29 +98,18 @@ impl Menu {
 
                 cmd
             }
-            Some(current) if current == menu_type => {
-                self.menu_type = None;
-                if let Some(id) = self.id.take() {
-                    close_menu(id)
-                } else {
-                    iced::Command::none()
-                }
-            }
+            Some(current) if current == menu_type => self.close(),
             Some(_) => {
                 self.menu_type = Some(menu_type);
-                iced::Command::none()
+                // the surface is reused, drop the keyboard grab of the previous menu
+                self.unset_keyboard_interactivity()
             }
         }
     }
 
     pub fn close_if<Msg>(&mut self, menu_type: MenuType) -> Command<Msg> {
         if self.menu_type == Some(menu_type) {
-            self.menu_type = None;
-            if let Some(id) = self.id.take() {
-                close_menu(id)
-            } else {
-                iced::Command::none()
-            }
+            self.close()
         } else {
             iced::Command::none()
         }
@@ -120,8 +118,11 @@ impl Menu {
     pub fn close<Msg>(&mut self) -> Command<Msg> {
         self.menu_type = None;
 
-        if let Some(id) = self.id.take() {
-            close_menu(id)
+        if let Some(id) = self.id {
+            let unset = self.unset_keyboard_interactivity();
+            self.id = None;
+
+            iced::Command::batch(vec![unset, close_menu(id)])
         } else {
             iced::Command::none()
         }
@@ -0,0 +1,285 @@
+use crate::{
+    components::icons::{icon, Icons},
+    config::DisksModuleConfig,
+    menu::{Menu, MenuType},
+    modules::system_info::{level, metric_view},
+    style::HeaderButtonStyle,
+    utils::{format_bytes, format_rate},
+};
+use iced::{
+    theme::Button,
+    widget::{button, column, horizontal_rule, progress_bar, row, text, Column},
+    Alignment, Element, Length,
+};
+use log::warn;
+use std::{
+    collections::{HashMap, HashSet},
+    ffi::OsString,
+    fs,
+    path::Path,
+    time::{Duration, Instant},
+};
+use sysinfo::{Disk, Disks as SysDisks};
+
+/// /proc/diskstats counts sectors of 512 bytes, regardless of the device sector size
+const SECTOR_SIZE: f64 = 512.;
+
+#[derive(Debug, Clone, Copy, Default)]
+struct IoRate {
+    read: f64,
+    write: f64,
+}
+
+/// Read and written sectors for each block device
+fn get_disk_stats() -> HashMap<String, (u64, u64)> {
+    match fs::read_to_string("/proc/diskstats") {
+        Ok(stats) => stats
+            .lines()
+            .filter_map(|line| {
+                let fields = line.split_whitespace().collect::<Vec<_>>();
+                let read = fields.get(5)?.parse().ok()?;
+                let written = fields.get(9)?.parse().ok()?;
+
+                Some((fields.get(2)?.to_string(), (read, written)))
+            })
+            .collect(),
+        Err(e) => {
+            warn!("Failed to read disk stats: {:?}", e);
+            HashMap::new()
+        }
+    }
+}
+
+/// Kernel name of the disk block device, `/dev/mapper` links are resolved to the `dm-*` device
+fn device_name(disk: &Disk) -> Option<String> {
+    let path = Path::new(disk.name());
+
+    fs::canonicalize(path)
+        .unwrap_or_else(|_| path.to_path_buf())
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+}
+
+/// Kernel device names keyed by the disk name, resolved when the disk list is refreshed
+fn device_names(disks: &SysDisks) -> HashMap<OsString, String> {
+    disks
+        .iter()
+        .filter_map(|disk| Some((disk.name().to_os_string(), device_name(disk)?)))
+        .collect()
+}
+
+fn used_percentage(disk: &Disk) -> u32 {
+    if disk.total_space() == 0 {
+        0
+    } else {
+        ((disk.total_space() - disk.available_space()) as f64 / disk.total_space() as f64 * 100.)
+            as u32
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Update(Instant),
+    ToggleMenu,
+}
+
+pub struct Disks {
+    disks: SysDisks,
+    devices: HashMap<OsString, String>,
+    stats: HashMap<String, (u64, u64)>,
+    rates: HashMap<String, IoRate>,
+    last_update: Instant,
+}
+
+impl Disks {
+    pub fn new() -> Self {
+        let disks = SysDisks::new_with_refreshed_list();
+
+        Self {
+            devices: device_names(&disks),
+            disks,
+            stats: get_disk_stats(),
+            rates: HashMap::new(),
+            last_update: Instant::now(),
+        }
+    }
+
+    fn watched<'a>(&'a self, config: &'a DisksModuleConfig) -> impl Iterator<Item = &'a Disk> {
+        config.mounts.iter().filter_map(|mount| {
+            self.disks
+                .iter()
+                .find(|d| d.mount_point() == Path::new(mount))
+        })
+    }
+
+    fn device(&self, disk: &Disk) -> Option<&String> {
+        self.devices.get(disk.name())
+    }
+
+    fn rate(&self, disk: &Disk) -> IoRate {
+        self.device(disk)
+            .and_then(|name| self.rates.get(name).copied())
+            .unwrap_or_default()
+    }
+
+    pub fn update(&mut self, message: Message, menu: &mut Menu) -> iced::Command<Message> {
+        match message {
+            Message::Update(now) => {
+                // mounts can change at any time, e.g. removable drives
+                self.disks.refresh_list();
+                self.devices = device_names(&self.disks);
+
+                let stats = get_disk_stats();
+                let elapsed = now.duration_since(self.last_update).as_secs_f64();
+                if elapsed > 0. {
+                    self.rates = stats
+                        .iter()
+                        .filter_map(|(device, (read, written))| {
+                            let (prev_read, prev_written) = self.stats.get(device)?;
+
+                            Some((
+                                device.clone(),
+                                IoRate {
+                                    read: read.saturating_sub(*prev_read) as f64 * SECTOR_SIZE
+                                        / elapsed,
+                                    write: written.saturating_sub(*prev_written) as f64
+                                        * SECTOR_SIZE
+                                        / elapsed,
+                                },
+                            ))
+                        })
+                        .collect();
+                }
+                self.stats = stats;
+                self.last_update = now;
+
+                iced::Command::none()
+            }
+            Message::ToggleMenu => menu.toggle(MenuType::Disks),
+        }
+    }
+
+    pub fn view(&self, config: &DisksModuleConfig) -> Option<Element<Message>> {
+        if config.disabled {
+            return None;
+        }
+
+        let disks = self
+            .watched(config)
+            .map(|disk| {
+                let usage = used_percentage(disk);
+
+                metric_view(
+                    column!(icon(Icons::Disk), text(format!("{}", usage)))
+                        .spacing(4)
+                        .align_items(Alignment::Center),
+                    level(
+                        usage,
+                        config.usage_warn_threshold,
+                        config.usage_alert_threshold,
+                    ),
+                )
+            })
+            .collect::<Vec<Element<'_, _, _>>>();
+
+        if disks.is_empty() {
+            return None;
+        }
+
+        let rate = self
+            .watched(config)
+            .filter_map(|disk| self.device(disk))
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .filter_map(|name| self.rates.get(name))
+            .fold(IoRate::default(), |acc, rate| IoRate {
+                read: acc.read + rate.read,
+                write: acc.write + rate.write,
+            });
+
+        Some(
+            button(
+                column!(
+                    Column::with_children(disks)
+                        .spacing(4)
+                        .align_items(Alignment::Center),
+                    column!(
+                        text(format!("R {}", format_rate(rate.read))).size(8),
+                        text(format!("W {}", format_rate(rate.write))).size(8),
+                    )
+                    .align_items(Alignment::Center)
+                )
+                .spacing(4)
+                .align_items(Alignment::Center),
+            )
+            .width(Length::Fill)
+            .padding([4, 0])
+            .style(Button::custom(HeaderButtonStyle::Full))
+            .on_press(Message::ToggleMenu)
+            .into(),
+        )
+    }
+
+    pub fn menu_view(&self, config: &DisksModuleConfig) -> Element<Message> {
+        Column::with_children(
+            self.disks
+                .iter()
+                .map(|disk| {
+                    let usage = used_percentage(disk);
+                    let rate = self.rate(disk);
+
+                    metric_view(
+                        column!(
+                            row!(
+                                text(disk.mount_point().to_string_lossy()).size(12),
+                                text(format!(
+                                    "{} {}",
+                                    disk.name().to_string_lossy(),
+                                    disk.file_system().to_string_lossy()
+                                ))
+                                .size(10)
+                                .width(Length::Fill),
+                                text(format!(
+                                    "{} free of {}",
+                                    format_bytes(disk.available_space() as f64),
+                                    format_bytes(disk.total_space() as f64)
+                                ))
+                                .size(12),
+                            )
+                            .spacing(8)
+                            .align_items(Alignment::Center),
+                            progress_bar(0.0..=100.0, usage as f32).height(Length::Fixed(4.)),
+                            text(format!(
+                                "read {} write {}",
+                                format_rate(rate.read),
+                                format_rate(rate.write)
+                            ))
+                            .size(10),
+                        )
+                        .spacing(4),
+                        level(
+                            usage,
+                            config.usage_warn_threshold,
+                            config.usage_alert_threshold,
+                        ),
+                    )
+                })
+                .collect::<Vec<Element<'_, _, _>>>()
+                .into_iter()
+                .enumerate()
+                .flat_map(|(index, disk)| {
+                    [(index > 0).then(|| horizontal_rule(1).into()), Some(disk)]
+                })
+                .flatten()
+                .collect::<Vec<_>>(),
+        )
+        .spacing(8)
+        .padding(16)
+        .width(400)
+        .into()
+    }
+
+    pub fn subscription(&self, config: &DisksModuleConfig) -> iced::Subscription<Message> {
+        iced::time::every(Duration::from_secs(config.interval.max(1))).map(Message::Update)
+    }
+}
@@ -0,0 +1,477 @@
+use crate::{
+    components::icons::{icon, Icons},
+    config::DisplaysModuleConfig,
+    menu::{Menu, MenuType},
+    style::{GhostButtonStyle, HeaderButtonStyle, QuickSettingsButtonStyle, SettingsButtonStyle},
+};
+use hyprland::{event_listener::EventListener, keyword::Keyword};
+use iced::{
+    futures::SinkExt,
+    theme::Button,
+    widget::{button, column, horizontal_rule, row, text, text_input, Column, Row},
+    Alignment, Element, Length,
+};
+use log::error;
+use serde::Deserialize;
+use std::{cell::RefCell, process::Stdio};
+use tokio::process::Command;
+
+const SCALE_STEP: f32 = 0.25;
+
+#[derive(Debug, Clone)]
+pub struct Display {
+    pub name: String,
+    pub description: String,
+    pub mode: String,
+    pub position: String,
+    pub scale: f32,
+    pub transform: u8,
+    pub enabled: bool,
+    pub mirror: Option<String>,
+}
+
+/// Monitor as reported by `hyprctl monitors all -j`, disabled outputs included
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct HyprMonitor {
+    name: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    width: u16,
+    #[serde(default)]
+    height: u16,
+    #[serde(default)]
+    refresh_rate: f32,
+    #[serde(default)]
+    x: i32,
+    #[serde(default)]
+    y: i32,
+    #[serde(default = "default_scale")]
+    scale: f32,
+    #[serde(default)]
+    transform: u8,
+    #[serde(default)]
+    disabled: bool,
+    #[serde(default)]
+    mirror_of: String,
+}
+
+fn default_scale() -> f32 {
+    1.
+}
+
+impl From<HyprMonitor> for Display {
+    fn from(monitor: HyprMonitor) -> Self {
+        Self {
+            // disabled outputs may report an empty mode
+            mode: if monitor.width == 0 || monitor.height == 0 {
+                "preferred".to_string()
+            } else {
+                format!(
+                    "{}x{}@{:.2}",
+                    monitor.width, monitor.height, monitor.refresh_rate
+                )
+            },
+            position: format!("{}x{}", monitor.x, monitor.y),
+            scale: monitor.scale,
+            transform: monitor.transform,
+            enabled: !monitor.disabled,
+            mirror: Some(monitor.mirror_of).filter(|m| !m.is_empty() && m != "none"),
+            name: monitor.name,
+            description: monitor.description,
+        }
+    }
+}
+
+impl Display {
+    /// Hyprland monitor rule, the same format used by `hyprctl keyword monitor`
+    pub fn rule(&self) -> String {
+        if !self.enabled {
+            return format!("{},disable", self.name);
+        }
+
+        let mut rule = format!(
+            "{},{},{},{}",
+            self.name, self.mode, self.position, self.scale
+        );
+        if self.transform != 0 {
+            rule.push_str(&format!(",transform,{}", self.transform));
+        }
+        if let Some(mirror) = &self.mirror {
+            rule.push_str(&format!(",mirror,{}", mirror));
+        }
+
+        rule
+    }
+}
+
+fn set_monitor_rule(rule: &str) {
+    if let Err(e) = Keyword::set("monitor", rule) {
+        error!("failed to apply monitor rule {}: {:?}", rule, e);
+    }
+}
+
+async fn get_displays() -> Vec<Display> {
+    let monitors_cmd = Command::new("hyprctl")
+        .args(["monitors", "all", "-j"])
+        .stdout(Stdio::piped())
+        .output()
+        .await;
+
+    match monitors_cmd {
+        Ok(monitors_cmd) => serde_json::from_slice::<Vec<HyprMonitor>>(&monitors_cmd.stdout)
+            .map(|monitors| monitors.into_iter().map(Display::from).collect())
+            .unwrap_or_else(|e| {
+                error!("Failed to parse monitors: {:?}", e);
+                vec![]
+            }),
+        Err(e) => {
+            error!("Error: {:?}", e);
+            vec![]
+        }
+    }
+}
+
+fn refresh() -> iced::Command<Message> {
+    iced::Command::perform(get_displays(), Message::DisplaysChanged)
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    ToggleMenu,
+    Refresh,
+    DisplaysChanged(Vec<Display>),
+    ModeChanged(String, String),
+    PositionChanged(String, String),
+    Apply(String),
+    Scale(String, f32),
+    Rotate(String),
+    Flip(String),
+    ToggleEnabled(String),
+    Mirror(String, Option<String>),
+    ApplyLayout(usize),
+}
+
+pub struct Displays {
+    displays: Vec<Display>,
+}
+
+impl Displays {
+    pub fn new() -> Self {
+        Self {
+            displays: Vec::new(),
+        }
+    }
+
+    fn apply(&mut self, name: &str, f: impl FnOnce(&mut Display)) -> iced::Command<Message> {
+        if let Some(display) = self.displays.iter_mut().find(|d| d.name == name) {
+            f(display);
+            set_monitor_rule(&display.rule());
+        }
+
+        refresh()
+    }
+
+    pub fn update(
+        &mut self,
+        message: Message,
+        config: &DisplaysModuleConfig,
+        menu: &mut Menu,
+    ) -> iced::Command<Message> {
+        match message {
+            Message::ToggleMenu => iced::Command::batch(vec![
+                menu.toggle(MenuType::Displays),
+                menu.set_keyboard_interactivity(),
+                refresh(),
+            ]),
+            Message::Refresh => refresh(),
+            Message::DisplaysChanged(displays) => {
+                self.displays = displays;
+
+                iced::Command::none()
+            }
+            Message::ModeChanged(name, mode) => {
+                if let Some(display) = self.displays.iter_mut().find(|d| d.name == name) {
+                    display.mode = mode;
+                }
+
+                iced::Command::none()
+            }
+            Message::PositionChanged(name, position) => {
+                if let Some(display) = self.displays.iter_mut().find(|d| d.name == name) {
+                    display.position = position;
+                }
+
+                iced::Command::none()
+            }
+            Message::Apply(name) => self.apply(&name, |_| {}),
+            Message::Scale(name, step) => {
+                self.apply(&name, |d| d.scale = (d.scale + step).clamp(SCALE_STEP, 4.))
+            }
+            Message::Rotate(name) => {
+                // keep the flipped bit while rotating by 90 degrees
+                self.apply(&name, |d| {
+                    d.transform = (d.transform & 4) | ((d.transform + 1) % 4)
+                })
+            }
+            Message::Flip(name) => self.apply(&name, |d| d.transform ^= 4),
+            Message::ToggleEnabled(name) => {
+                let enabled_count = self.displays.iter().filter(|d| d.enabled).count();
+                let is_last = self
+                    .displays
+                    .iter()
+                    .any(|d| d.name == name && d.enabled && enabled_count == 1);
+
+                // never turn off the last active output
+                if is_last {
+                    iced::Command::none()
+                } else {
+                    self.apply(&name, |d| d.enabled = !d.enabled)
+                }
+            }
+            Message::Mirror(name, mirror) => self.apply(&name, |d| d.mirror = mirror),
+            Message::ApplyLayout(index) => {
+                if let Some(layout) = config.layouts.get(index) {
+                    for rule in &layout.monitors {
+                        set_monitor_rule(rule);
+                    }
+                }
+
+                refresh()
+            }
+        }
+    }
+
+    pub fn view(&self) -> Element<Message> {
+        button(icon(Icons::Display))
+            .padding([2, 7])
+            .style(Button::custom(HeaderButtonStyle::Full))
+            .on_press(Message::ToggleMenu)
+            .into()
+    }
+
+    fn display_view<'a>(&'a self, display: &'a Display) -> Element<'a, Message> {
+        let name = display.name.clone();
+
+        let header = row!(
+            column!(
+                text(&display.name).size(14),
+                text(&display.description).size(10)
+            )
+            .spacing(2)
+            .width(Length::Fill),
+            button(icon(Icons::Power))
+                .padding([4, 8])
+                .on_press(Message::ToggleEnabled(name.clone()))
+                .style(Button::custom(QuickSettingsButtonStyle(display.enabled)))
+        )
+        .spacing(8)
+        .align_items(Alignment::Center);
+
+        if !display.enabled {
+            return header.into();
+        }
+
+        let settings = column!(
+            row!(
+                text("Mode").size(12).width(Length::Fixed(70.)),
+                text_input("preferred", &display.mode)
+                    .size(12)
+                    .on_input({
+                        let name = name.clone();
+                        move |mode| Message::ModeChanged(name.clone(), mode)
+                    })
+                    .on_submit(Message::Apply(name.clone())),
+            )
+            .spacing(8)
+            .align_items(Alignment::Center),
+            row!(
+                text("Position").size(12).width(Length::Fixed(70.)),
+                text_input("auto", &display.position)
+                    .size(12)
+                    .on_input({
+                        let name = name.clone();
+                        move |position| Message::PositionChanged(name.clone(), position)
+                    })
+                    .on_submit(Message::Apply(name.clone())),
+            )
+            .spacing(8)
+            .align_items(Alignment::Center),
+            row!(
+                text("Scale").size(12).width(Length::Fixed(70.)),
+                button(text("-").size(12))
+                    .padding([2, 8])
+                    .on_press(Message::Scale(name.clone(), -SCALE_STEP))
+                    .style(Button::custom(SettingsButtonStyle)),
+                text(format!("{:.2}", display.scale)).size(12),
+                button(text("+").size(12))
+                    .padding([2, 8])
+                    .on_press(Message::Scale(name.clone(), SCALE_STEP))
+                    .style(Button::custom(SettingsButtonStyle)),
+            )
+            .spacing(8)
+            .align_items(Alignment::Center),
+            row!(
+                text("Transform").size(12).width(Length::Fixed(70.)),
+                button(icon(Icons::Rotate))
+                    .padding([2, 8])
+                    .on_press(Message::Rotate(name.clone()))
+                    .style(Button::custom(SettingsButtonStyle)),
+                button(icon(Icons::Flip))
+                    .padding([2, 8])
+                    .on_press(Message::Flip(name.clone()))
+                    .style(Button::custom(QuickSettingsButtonStyle(
+                        display.transform & 4 != 0
+                    ))),
+                text(format!("{}°", (display.transform % 4) as u32 * 90)).size(12),
+            )
+            .spacing(8)
+            .align_items(Alignment::Center),
+        )
+        .spacing(8);
+
+        let others = self
+            .displays
+            .iter()
+            .filter(|d| d.enabled && d.name != display.name)
+            .collect::<Vec<_>>();
+
+        let mirror = if others.is_empty() {
+            None
+        } else {
+            Some(
+                row!(
+                    text("Mirror").size(12).width(Length::Fixed(70.)),
+                    Row::with_children(
+                        std::iter::once(
+                            button(text("None").size(12))
+                                .padding([2, 8])
+                                .on_press(Message::Mirror(name.clone(), None))
+                                .style(Button::custom(QuickSettingsButtonStyle(
+                                    display.mirror.is_none()
+                                )))
+                                .into(),
+                        )
+                        .chain(others.into_iter().map(|other| {
+                            button(text(&other.name).size(12))
+                                .padding([2, 8])
+                                .on_press(Message::Mirror(name.clone(), Some(other.name.clone())))
+                                .style(Button::custom(QuickSettingsButtonStyle(
+                                    display.mirror.as_ref() == Some(&other.name),
+                                )))
+                                .into()
+                        }))
+                        .collect::<Vec<Element<'_, _, _>>>(),
+                    )
+                    .spacing(4),
+                )
+                .spacing(8)
+                .align_items(Alignment::Center),
+            )
+        };
+
+        Column::with_children(
+            vec![
+                Some(header.into()),
+                Some(settings.into()),
+                mirror.map(Into::into),
+            ]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<Element<'_, _, _>>>(),
+        )
+        .spacing(12)
+        .into()
+    }
+
+    pub fn menu_view(&self, config: &DisplaysModuleConfig) -> Element<Message> {
+        let layouts = if config.layouts.is_empty() {
+            None
+        } else {
+            Some(
+                column!(
+                    text("Layouts").size(12),
+                    Column::with_children(
+                        config
+                            .layouts
+                            .iter()
+                            .enumerate()
+                            .map(|(index, layout)| {
+                                button(text(&layout.name).size(12))
+                                    .padding([4, 8])
+                                    .width(Length::Fill)
+                                    .on_press(Message::ApplyLayout(index))
+                                    .style(Button::custom(GhostButtonStyle))
+                                    .into()
+                            })
+                            .collect::<Vec<Element<'_, _, _>>>(),
+                    )
+                    .spacing(2),
+                    horizontal_rule(1),
+                )
+                .spacing(8)
+                .into(),
+            )
+        };
+
+        Column::with_children(
+            layouts
+                .into_iter()
+                .chain(
+                    self.displays
+                        .iter()
+                        .enumerate()
+                        .flat_map(|(index, display)| {
+                            [
+                                (index > 0).then(|| horizontal_rule(1).into()),
+                                Some(self.display_view(display)),
+                            ]
+                        })
+                        .flatten(),
+                )
+                .collect::<Vec<_>>(),
+        )
+        .spacing(16)
+        .padding(16)
+        .width(400)
+        .into()
+    }
+
+    pub fn subscription(&self) -> iced::Subscription<Message> {
+        iced::subscription::channel("displays-listener", 10, |mut output| async move {
+            // load the outputs once the module is known to be enabled
+            let _ = output.send(Message::Refresh).await;
+
+            let output = RefCell::new(output);
+            let mut event_listener = EventListener::new();
+
+            event_listener.add_monitor_added_handler({
+                let output = output.clone();
+                move |_| {
+                    let mut output = output.borrow_mut();
+                    output
+                        .try_send(Message::Refresh)
+                        .expect("error getting displays: monitor added event");
+                }
+            });
+
+            event_listener.add_monitor_removed_handler({
+                let output = output.clone();
+                move |_| {
+                    let mut output = output.borrow_mut();
+                    output
+                        .try_send(Message::Refresh)
+                        .expect("error getting displays: monitor removed event");
+                }
+            });
+
+            event_listener
+                .start_listener_async()
+                .await
+                .expect("failed to start displays listener");
+
+            panic!("Exiting hyprland event listener");
+        })
+    }
+}
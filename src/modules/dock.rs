@@ -0,0 +1,347 @@
+use crate::{
+    components::icons::{app_icon, icon, Icons},
+    config::DockModuleConfig,
+    menu::{Menu, MenuType},
+    style::GhostButtonStyle,
+    utils::{
+        desktop_entries::{load_desktop_entries, DesktopEntry},
+        launcher::execute_command,
+    },
+};
+use hyprland::{
+    data::{Client, Clients},
+    dispatch::{Dispatch, DispatchType},
+    event_listener::EventListener,
+    shared::{HyprData, HyprDataActiveOptional, HyprDataVec},
+};
+use iced::{
+    theme::Button,
+    widget::{button, column, container, horizontal_rule, mouse_area, text, Column},
+    Alignment, Element, Length, Theme,
+};
+use log::{error, warn};
+use std::cell::RefCell;
+
+/// Max number of running indicators shown below an icon
+const MAX_DOTS: usize = 3;
+
+/// Max number of characters of the name shown for apps without a matching glyph
+const MAX_LABEL_LENGTH: usize = 6;
+
+#[derive(Debug, Clone)]
+pub struct DockClient {
+    pub address: String,
+    pub class: String,
+    pub active: bool,
+}
+
+/// Glyph matching the entry icon name, app id or name
+fn entry_icon(entry: &DesktopEntry) -> Option<Icons> {
+    [
+        entry.icon.as_deref(),
+        Some(entry.app_id()),
+        Some(entry.name.as_str()),
+    ]
+    .into_iter()
+    .flatten()
+    .map(app_icon)
+    .find(|glyph| *glyph != Icons::AppGeneric)
+}
+
+/// First word of the app name, it fits below the icon in the bar
+fn short_name(name: &str) -> String {
+    name.split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .take(MAX_LABEL_LENGTH)
+        .collect()
+}
+
+fn get_clients() -> Vec<DockClient> {
+    let active = Client::get_active()
+        .ok()
+        .flatten()
+        .map(|c| c.address.to_string());
+
+    Clients::get()
+        .map(|c| c.to_vec())
+        .unwrap_or_default()
+        .into_iter()
+        .map(|c| {
+            let address = c.address.to_string();
+            DockClient {
+                active: active.as_ref() == Some(&address),
+                address,
+                class: c.class,
+            }
+        })
+        .collect()
+}
+
+fn focus_window(address: &str) {
+    if let Err(e) = Dispatch::call(DispatchType::Custom(
+        "focuswindow",
+        &format!("address:{}", address),
+    )) {
+        error!("failed to focus window {}: {:?}", address, e);
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    EntriesLoaded(Vec<DesktopEntry>),
+    ClientsChanged(Vec<DockClient>),
+    Activate(String),
+    Launch(String, Option<usize>),
+    OpenMenu(String),
+}
+
+pub struct Dock {
+    entries: Vec<DesktopEntry>,
+    clients: Vec<DockClient>,
+    menu_entry: Option<String>,
+}
+
+impl Dock {
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            clients: Vec::new(),
+            menu_entry: None,
+        }
+    }
+
+    /// Pinned desktop entries, in the configured order
+    fn pinned<'a>(
+        &'a self,
+        config: &'a DockModuleConfig,
+    ) -> impl Iterator<Item = &'a DesktopEntry> {
+        config.pinned.iter().filter_map(|id| {
+            self.entries
+                .iter()
+                .find(|e| e.id == *id || e.app_id() == id)
+        })
+    }
+
+    fn entry(&self, id: &str) -> Option<&DesktopEntry> {
+        self.entries.iter().find(|e| e.id == id)
+    }
+
+    fn instances(&self, entry: &DesktopEntry) -> Vec<&DockClient> {
+        self.clients
+            .iter()
+            .filter(|c| entry.matches_class(&c.class))
+            .collect()
+    }
+
+    pub fn update(
+        &mut self,
+        message: Message,
+        config: &DockModuleConfig,
+        terminal_cmd: &str,
+        menu: &mut Menu,
+    ) -> iced::Command<Message> {
+        match message {
+            Message::EntriesLoaded(entries) => {
+                for id in config.pinned.iter() {
+                    if !entries.iter().any(|e| e.id == *id || e.app_id() == id) {
+                        warn!("Pinned application {} not found", id);
+                    }
+                }
+                self.entries = entries;
+
+                iced::Command::none()
+            }
+            Message::ClientsChanged(clients) => {
+                self.clients = clients;
+
+                iced::Command::none()
+            }
+            Message::Activate(id) => {
+                let Some(entry) = self.entry(&id) else {
+                    return iced::Command::none();
+                };
+                let instances = self.instances(entry);
+
+                if instances.is_empty() {
+                    if let Some(command) = entry.command(None, terminal_cmd) {
+                        execute_command(command);
+                    }
+                } else {
+                    // cycle through the app windows starting from the active one
+                    let next = instances
+                        .iter()
+                        .position(|c| c.active)
+                        .map_or(0, |i| (i + 1) % instances.len());
+
+                    focus_window(&instances[next].address);
+                }
+
+                iced::Command::none()
+            }
+            Message::Launch(id, action) => {
+                if let Some(command) = self
+                    .entry(&id)
+                    .and_then(|e| e.command(action, terminal_cmd))
+                {
+                    execute_command(command);
+                }
+                self.menu_entry = None;
+
+                menu.close_if(MenuType::Dock)
+            }
+            Message::OpenMenu(id) => {
+                let toggle = self.menu_entry.as_ref() == Some(&id)
+                    || menu.get_menu_type() != Some(MenuType::Dock);
+                self.menu_entry = Some(id);
+
+                if toggle {
+                    menu.toggle(MenuType::Dock)
+                } else {
+                    iced::Command::none()
+                }
+            }
+        }
+    }
+
+    pub fn view(&self, config: &DockModuleConfig) -> Option<Element<Message>> {
+        if config.disabled {
+            return None;
+        }
+
+        let apps = self
+            .pinned(config)
+            .map(|entry| {
+                let instances = self.instances(entry);
+                let active = instances.iter().any(|c| c.active);
+                let glyph = entry_icon(entry);
+
+                mouse_area(
+                    container(
+                        column!(
+                            icon(glyph.unwrap_or(Icons::AppGeneric)),
+                            text(if glyph.is_none() {
+                                short_name(&entry.name)
+                            } else {
+                                String::new()
+                            })
+                            .size(8),
+                            text("•".repeat(instances.len().min(MAX_DOTS))).size(8)
+                        )
+                        .align_items(Alignment::Center),
+                    )
+                    .padding([2, 4])
+                    .style(move |theme: &Theme| container::Appearance {
+                        text_color: if active {
+                            Some(theme.palette().primary)
+                        } else {
+                            None
+                        },
+                        ..Default::default()
+                    }),
+                )
+                .on_release(Message::Activate(entry.id.clone()))
+                .on_right_release(Message::OpenMenu(entry.id.clone()))
+                .into()
+            })
+            .collect::<Vec<Element<'_, _, _>>>();
+
+        if apps.is_empty() {
+            None
+        } else {
+            Some(
+                Column::with_children(apps)
+                    .align_items(Alignment::Center)
+                    .spacing(4)
+                    .into(),
+            )
+        }
+    }
+
+    pub fn menu_view(&self) -> Element<Message> {
+        let Some(entry) = self.menu_entry.as_ref().and_then(|id| self.entry(id)) else {
+            return column!().into();
+        };
+
+        let action_button = |name: &str, action: Option<usize>| -> Element<Message> {
+            button(text(name.to_string()).size(12))
+                .padding([4, 8])
+                .width(Length::Fill)
+                .on_press(Message::Launch(entry.id.clone(), action))
+                .style(Button::custom(GhostButtonStyle))
+                .into()
+        };
+
+        column!(
+            text(&entry.name).size(14),
+            horizontal_rule(1),
+            Column::with_children(
+                std::iter::once(action_button("New window", None))
+                    .chain(
+                        entry
+                            .actions
+                            .iter()
+                            .enumerate()
+                            .map(|(index, action)| action_button(&action.name, Some(index))),
+                    )
+                    .collect::<Vec<Element<'_, _, _>>>(),
+            )
+            .spacing(2),
+        )
+        .spacing(8)
+        .padding(16)
+        .width(250)
+        .into()
+    }
+
+    pub fn subscription(&self) -> iced::Subscription<Message> {
+        iced::subscription::channel("dock-listener", 10, |output| async move {
+            let entries = load_desktop_entries().await;
+
+            let output = RefCell::new(output);
+            let _ = output
+                .borrow_mut()
+                .try_send(Message::EntriesLoaded(entries));
+            let _ = output
+                .borrow_mut()
+                .try_send(Message::ClientsChanged(get_clients()));
+
+            let mut event_listener = EventListener::new();
+
+            event_listener.add_window_open_handler({
+                let output = output.clone();
+                move |_| {
+                    let _ = output
+                        .borrow_mut()
+                        .try_send(Message::ClientsChanged(get_clients()));
+                }
+            });
+
+            event_listener.add_window_close_handler({
+                let output = output.clone();
+                move |_| {
+                    let _ = output
+                        .borrow_mut()
+                        .try_send(Message::ClientsChanged(get_clients()));
+                }
+            });
+
+            event_listener.add_active_window_change_handler({
+                let output = output.clone();
+                move |_| {
+                    let _ = output
+                        .borrow_mut()
+                        .try_send(Message::ClientsChanged(get_clients()));
+                }
+            });
+
+            event_listener
+                .start_listener_async()
+                .await
+                .expect("failed to start dock listener");
+
+            panic!("Exiting hyprland event listener");
+        })
+    }
+}
@@ -0,0 +1,221 @@
+use crate::{
+    components::icons::{icon, Icons},
+    config::KeybindsModuleConfig,
+    menu::{Menu, MenuType},
+    style::{GhostButtonStyle, HeaderButtonStyle},
+};
+use hyprland::dispatch::{Dispatch, DispatchType};
+use iced::{
+    theme::Button,
+    widget::{
+        button, column, container, horizontal_rule, row, scrollable, text, text_input, Column,
+    },
+    Alignment, Element, Length,
+};
+use itertools::Itertools;
+use log::error;
+use serde::Deserialize;
+use std::process::Stdio;
+use tokio::process::Command;
+
+const MODIFIERS: [(u32, &str); 8] = [
+    (64, "SUPER"),
+    (4, "CTRL"),
+    (8, "ALT"),
+    (1, "SHIFT"),
+    (2, "CAPS"),
+    (16, "MOD2"),
+    (32, "MOD3"),
+    (128, "MOD5"),
+];
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Keybind {
+    #[serde(default)]
+    pub modmask: u32,
+    #[serde(default)]
+    pub submap: String,
+    #[serde(default)]
+    pub key: String,
+    #[serde(default)]
+    pub dispatcher: String,
+    #[serde(default)]
+    pub arg: String,
+}
+
+impl Keybind {
+    pub fn combo(&self) -> String {
+        MODIFIERS
+            .iter()
+            .filter(|(mask, _)| self.modmask & mask != 0)
+            .map(|(_, name)| *name)
+            .chain(std::iter::once(self.key.as_str()))
+            .join("+")
+    }
+
+    pub fn action(&self) -> String {
+        if self.arg.is_empty() {
+            self.dispatcher.clone()
+        } else {
+            format!("{} {}", self.dispatcher, self.arg)
+        }
+    }
+}
+
+async fn get_keybinds() -> Vec<Keybind> {
+    let binds_cmd = Command::new("hyprctl")
+        .args(["binds", "-j"])
+        .stdout(Stdio::piped())
+        .output()
+        .await;
+
+    match binds_cmd {
+        Ok(binds_cmd) => serde_json::from_slice(&binds_cmd.stdout).unwrap_or_else(|e| {
+            error!("Failed to parse keybinds: {:?}", e);
+            vec![]
+        }),
+        Err(e) => {
+            error!("Error: {:?}", e);
+            vec![]
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    ToggleMenu,
+    KeybindsLoaded(Vec<Keybind>),
+    SearchChanged(String),
+    Dispatch(String, String),
+}
+
+pub struct Keybinds {
+    keybinds: Vec<Keybind>,
+    search: String,
+}
+
+impl Keybinds {
+    pub fn new() -> Self {
+        Self {
+            keybinds: Vec::new(),
+            search: String::new(),
+        }
+    }
+
+    pub fn update(&mut self, message: Message, menu: &mut Menu) -> iced::Command<Message> {
+        match message {
+            Message::ToggleMenu => {
+                self.search.clear();
+                iced::Command::batch(vec![
+                    menu.toggle(MenuType::Keybinds),
+                    menu.set_keyboard_interactivity(),
+                    iced::Command::perform(get_keybinds(), Message::KeybindsLoaded),
+                ])
+            }
+            Message::KeybindsLoaded(keybinds) => {
+                self.keybinds = keybinds;
+
+                iced::Command::none()
+            }
+            Message::SearchChanged(search) => {
+                self.search = search;
+
+                iced::Command::none()
+            }
+            Message::Dispatch(dispatcher, arg) => {
+                if let Err(e) = Dispatch::call(DispatchType::Custom(&dispatcher, &arg)) {
+                    error!("failed to dispatch {} {}: {:?}", dispatcher, arg, e);
+                }
+
+                iced::Command::batch(vec![
+                    menu.unset_keyboard_interactivity(),
+                    menu.close_if(MenuType::Keybinds),
+                ])
+            }
+        }
+    }
+
+    pub fn view(&self) -> Element<Message> {
+        button(icon(Icons::Keyboard))
+            .padding([2, 7])
+            .style(Button::custom(HeaderButtonStyle::Full))
+            .on_press(Message::ToggleMenu)
+            .into()
+    }
+
+    pub fn menu_view(&self, config: &KeybindsModuleConfig) -> Element<Message> {
+        let search = self.search.to_lowercase();
+
+        let groups = self
+            .keybinds
+            .iter()
+            .map(|k| {
+                let combo = k.combo();
+                let description = config.descriptions.get(&combo).cloned();
+                (k, combo, description)
+            })
+            .filter(|(k, combo, description)| {
+                search.is_empty()
+                    || combo.to_lowercase().contains(&search)
+                    || k.action().to_lowercase().contains(&search)
+                    || description
+                        .as_ref()
+                        .is_some_and(|d| d.to_lowercase().contains(&search))
+            })
+            .into_group_map_by(|(k, _, _)| {
+                if k.submap.is_empty() {
+                    k.dispatcher.clone()
+                } else {
+                    format!("{} ({})", k.dispatcher, k.submap)
+                }
+            })
+            .into_iter()
+            .sorted_by(|(a, _), (b, _)| a.cmp(b))
+            .map(|(group, keybinds)| {
+                column!(
+                    text(group).size(12),
+                    horizontal_rule(1),
+                    Column::with_children(
+                        keybinds
+                            .into_iter()
+                            .map(|(k, combo, description)| {
+                                button(
+                                    row!(
+                                        text(combo).size(12).width(Length::FillPortion(2)),
+                                        text(description.unwrap_or_else(|| k.action()))
+                                            .size(12)
+                                            .width(Length::FillPortion(3)),
+                                    )
+                                    .spacing(8)
+                                    .align_items(Alignment::Center),
+                                )
+                                .padding([4, 8])
+                                .width(Length::Fill)
+                                .on_press(Message::Dispatch(k.dispatcher.clone(), k.arg.clone()))
+                                .style(Button::custom(GhostButtonStyle))
+                                .into()
+                            })
+                            .collect::<Vec<Element<'_, _, _>>>(),
+                    )
+                    .spacing(2)
+                )
+                .spacing(4)
+                .into()
+            })
+            .collect::<Vec<Element<'_, _, _>>>();
+
+        column!(
+            text_input("Search keybindings", &self.search).on_input(Message::SearchChanged),
+            container(scrollable(
+                Column::with_children(groups)
+                    .spacing(12)
+                    .padding([0, 16, 0, 0])
+            ))
+            .max_height(500),
+        )
+        .spacing(16)
+        .padding(16)
+        .width(450)
+        .into()
+    }
+}
@@ -1,12 +1,346 @@
 use crate::{
-    app::Message, components::icons::{icon, Icons}, style::HeaderButtonStyle
+    components::icons::{app_icon, icon, Icons},
+    config::LauncherModuleConfig,
+    menu::{Menu, MenuType},
+    style::{GhostButtonStyle, HeaderButtonStyle, QuickSettingsButtonStyle, SettingsButtonStyle},
+    utils::{
+        desktop_entries::{load_desktop_entries, DesktopEntry},
+        launcher::execute_command,
+    },
 };
-use iced::{widget::button, Element};
+use iced::{
+    keyboard::{key::Named, Key},
+    theme::Button,
+    widget::{button, column, row, text, text_input, Column, Row},
+    Alignment, Element, Length,
+};
+use log::warn;
+use std::{collections::HashMap, env, fs, path::PathBuf};
+
+/// Launch counts of each desktop entry, used to rank the results
+fn frequency_path() -> PathBuf {
+    let cache_home = env::var("XDG_CACHE_HOME")
+        .ok()
+        .filter(|d| !d.is_empty())
+        .unwrap_or_else(|| format!("{}/.cache", env::var("HOME").unwrap_or_default()));
+
+    PathBuf::from(cache_home)
+        .join("ashell")
+        .join("launcher.json")
+}
+
+fn read_frequency() -> HashMap<String, u32> {
+    fs::read_to_string(frequency_path())
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn write_frequency(frequency: &HashMap<String, u32>) {
+    let path = frequency_path();
+    let result = path
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|_| fs::write(&path, serde_json::to_string(frequency).unwrap_or_default()));
+
+    if let Err(e) = result {
+        warn!("Failed to save the launcher frequency: {:?}", e);
+    }
+}
+
+/// Subsequence match of the query in the target, consecutive chars and
+/// word starts get a bonus. Both strings must be lowercase
+fn fuzzy_score(query: &str, target: &str) -> Option<i32> {
+    if query.is_empty() {
+        return Some(0);
+    }
+
+    let target = target.chars().collect::<Vec<_>>();
+    let mut score = 0;
+    let mut position = 0;
+    let mut previous: Option<usize> = None;
+
+    for c in query.chars() {
+        let index = position + target[position..].iter().position(|t| *t == c)?;
+
+        score += 1;
+        if previous.is_some_and(|p| p + 1 == index) {
+            score += 5;
+        }
+        if index == 0 || !target[index - 1].is_alphanumeric() {
+            score += 3;
+        }
+
+        previous = Some(index);
+        position = index + 1;
+    }
+
+    // prefer shorter targets, the query covers more of them
+    Some(score * 10 - target.len() as i32)
+}
+
+fn entry_score(query: &str, entry: &DesktopEntry) -> Option<i32> {
+    let name = fuzzy_score(query, &entry.name.to_lowercase()).map(|s| s + 100);
+    let others = entry
+        .generic_name
+        .iter()
+        .chain(entry.keywords.iter())
+        .chain(std::iter::once(&entry.exec))
+        .filter_map(|value| fuzzy_score(query, &value.to_lowercase()));
+
+    name.into_iter().chain(others).max()
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    ToggleMenu,
+    EntriesLoaded(Vec<DesktopEntry>),
+    SearchChanged(String),
+    SelectNext,
+    SelectPrevious,
+    LaunchSelected,
+    Launch(String, Option<usize>),
+    Close,
+}
+
+pub struct Launcher {
+    entries: Vec<DesktopEntry>,
+    frequency: HashMap<String, u32>,
+    search: String,
+    selected: usize,
+}
+
+impl Launcher {
+    pub const SEARCH_ID: &'static str = "launcher-search";
 
-pub fn launcher<'a>() -> Element<'a, Message> {
-    button(icon(Icons::Launcher))
-        .padding([2, 7])
-        .on_press(Message::OpenLauncher)
-        .style(iced::theme::Button::custom(HeaderButtonStyle::Full))
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            frequency: read_frequency(),
+            search: String::new(),
+            selected: 0,
+        }
+    }
+
+    /// Entries matching the search, sorted by score and frequency of use
+    fn results(&self, max_results: usize) -> Vec<&DesktopEntry> {
+        let query = self.search.trim().to_lowercase();
+
+        let mut results = self
+            .entries
+            .iter()
+            .filter_map(|entry| {
+                let frequency = self.frequency.get(&entry.id).copied().unwrap_or_default();
+
+                entry_score(&query, entry).map(|score| (score, frequency, entry))
+            })
+            .collect::<Vec<_>>();
+
+        results.sort_by(|(a_score, a_freq, a), (b_score, b_freq, b)| {
+            b_score
+                .cmp(a_score)
+                .then(b_freq.cmp(a_freq))
+                .then(a.name.cmp(&b.name))
+        });
+        // with an empty search the most used applications come first
+        if query.is_empty() {
+            results.sort_by(|(_, a_freq, a), (_, b_freq, b)| {
+                b_freq.cmp(a_freq).then(a.name.cmp(&b.name))
+            });
+        }
+
+        results
+            .into_iter()
+            .take(max_results)
+            .map(|(_, _, entry)| entry)
+            .collect()
+    }
+
+    fn launch(
+        &mut self,
+        id: &str,
+        action: Option<usize>,
+        config: &LauncherModuleConfig,
+        menu: &mut Menu,
+    ) -> iced::Command<Message> {
+        if let Some(command) = self
+            .entries
+            .iter()
+            .find(|e| e.id == id)
+            .and_then(|e| e.command(action, &config.terminal_cmd))
+        {
+            execute_command(command);
+
+            *self.frequency.entry(id.to_string()).or_default() += 1;
+            write_frequency(&self.frequency);
+        }
+
+        iced::Command::batch(vec![
+            menu.unset_keyboard_interactivity(),
+            menu.close_if(MenuType::Launcher),
+        ])
+    }
+
+    pub fn update(
+        &mut self,
+        message: Message,
+        config: &LauncherModuleConfig,
+        app_launcher_cmd: Option<&String>,
+        menu: &mut Menu,
+    ) -> iced::Command<Message> {
+        let is_open = menu.get_menu_type() == Some(MenuType::Launcher);
+
+        match message {
+            Message::ToggleMenu => {
+                // an external launcher takes precedence over the built-in one
+                if let Some(app_launcher_cmd) = app_launcher_cmd {
+                    execute_command(app_launcher_cmd.to_string());
+
+                    return iced::Command::none();
+                }
+
+                self.search.clear();
+                self.selected = 0;
+
+                iced::Command::batch(vec![
+                    menu.toggle(MenuType::Launcher),
+                    menu.set_keyboard_interactivity(),
+                    text_input::focus(text_input::Id::new(Self::SEARCH_ID)),
+                    iced::Command::perform(load_desktop_entries(), Message::EntriesLoaded),
+                ])
+            }
+            Message::EntriesLoaded(entries) => {
+                self.entries = entries;
+
+                iced::Command::none()
+            }
+            Message::SearchChanged(search) => {
+                self.search = search;
+                self.selected = 0;
+
+                iced::Command::none()
+            }
+            Message::SelectNext if is_open => {
+                let count = self.results(config.max_results).len();
+                if count > 0 {
+                    self.selected = (self.selected + 1) % count;
+                }
+
+                iced::Command::none()
+            }
+            Message::SelectPrevious if is_open => {
+                let count = self.results(config.max_results).len();
+                if count > 0 {
+                    self.selected = (self.selected + count - 1) % count;
+                }
+
+                iced::Command::none()
+            }
+            Message::LaunchSelected if is_open => {
+                match self
+                    .results(config.max_results)
+                    .get(self.selected)
+                    .map(|e| e.id.clone())
+                {
+                    Some(id) => self.launch(&id, None, config, menu),
+                    None => iced::Command::none(),
+                }
+            }
+            Message::Launch(id, action) => self.launch(&id, action, config, menu),
+            Message::Close if is_open => iced::Command::batch(vec![
+                menu.unset_keyboard_interactivity(),
+                menu.close_if(MenuType::Launcher),
+            ]),
+            _ => iced::Command::none(),
+        }
+    }
+
+    pub fn view(&self) -> Element<Message> {
+        button(icon(Icons::Launcher))
+            .padding([2, 7])
+            .on_press(Message::ToggleMenu)
+            .style(Button::custom(HeaderButtonStyle::Full))
+            .into()
+    }
+
+    pub fn menu_view(&self, config: &LauncherModuleConfig) -> Element<Message> {
+        let results = self
+            .results(config.max_results)
+            .into_iter()
+            .enumerate()
+            .map(|(index, entry)| {
+                let selected = index == self.selected;
+
+                let entry_button = button(
+                    row!(
+                        icon(app_icon(entry.icon.as_deref().unwrap_or(entry.app_id()))),
+                        column!(
+                            text(&entry.name).size(12),
+                            text(entry.generic_name.as_deref().unwrap_or_default()).size(10),
+                        )
+                        .width(Length::Fill),
+                        text(if entry.terminal { "terminal" } else { "" }).size(10),
+                    )
+                    .spacing(8)
+                    .align_items(Alignment::Center),
+                )
+                .padding([4, 8])
+                .width(Length::Fill)
+                .on_press(Message::Launch(entry.id.clone(), None))
+                .style(if selected {
+                    Button::custom(QuickSettingsButtonStyle(true))
+                } else {
+                    Button::custom(GhostButtonStyle)
+                });
+
+                // desktop actions of the selected entry, e.g. "New private window"
+                if selected && !entry.actions.is_empty() {
+                    column!(
+                        entry_button,
+                        Row::with_children(
+                            entry
+                                .actions
+                                .iter()
+                                .enumerate()
+                                .map(|(action, a)| {
+                                    button(text(&a.name).size(10))
+                                        .padding([2, 8])
+                                        .on_press(Message::Launch(entry.id.clone(), Some(action)))
+                                        .style(Button::custom(SettingsButtonStyle))
+                                        .into()
+                                })
+                                .collect::<Vec<Element<'_, _, _>>>(),
+                        )
+                        .spacing(4)
+                        .padding([0, 8]),
+                    )
+                    .spacing(4)
+                    .into()
+                } else {
+                    entry_button.into()
+                }
+            })
+            .collect::<Vec<Element<'_, _, _>>>();
+
+        column!(
+            text_input("Search applications", &self.search)
+                .id(text_input::Id::new(Self::SEARCH_ID))
+                .on_input(Message::SearchChanged)
+                .on_submit(Message::LaunchSelected),
+            Column::with_children(results).spacing(2),
+        )
+        .spacing(16)
+        .padding(16)
+        .width(400)
         .into()
+    }
+
+    pub fn subscription(&self) -> iced::Subscription<Message> {
+        iced::keyboard::on_key_press(|key, _| match key {
+            Key::Named(Named::ArrowDown) | Key::Named(Named::Tab) => Some(Message::SelectNext),
+            Key::Named(Named::ArrowUp) => Some(Message::SelectPrevious),
+            Key::Named(Named::Escape) => Some(Message::Close),
+            _ => None,
+        })
+    }
 }
@@ -1,7 +1,14 @@
 pub mod clock;
+pub mod disks;
+pub mod displays;
+pub mod dock;
+pub mod keybinds;
 pub mod launcher;
+pub mod network_speed;
 pub mod settings;
+pub mod submap;
 pub mod system_info;
+pub mod taskbar;
 pub mod title;
 pub mod updates;
 pub mod workspaces;
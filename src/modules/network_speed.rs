@@ -0,0 +1,221 @@
+use crate::{
+    components::{
+        icons::{icon, Icons},
+        sparkline::{push_sample, sparkline},
+    },
+    config::NetworkSpeedModuleConfig,
+    menu::{Menu, MenuType},
+    style::HeaderButtonStyle,
+    utils::format_rate,
+};
+use iced::{
+    theme::Button,
+    widget::{button, column, horizontal_rule, row, text, Column},
+    Alignment, Element, Length,
+};
+use log::warn;
+use nix::ifaddrs::getifaddrs;
+use std::{
+    collections::{BTreeMap, VecDeque},
+    net::{SocketAddrV4, SocketAddrV6},
+    time::{Duration, Instant},
+};
+use sysinfo::Networks;
+
+const HISTORY_SIZE: usize = 60;
+
+#[derive(Debug, Default)]
+struct InterfaceSpeed {
+    download: f64,
+    upload: f64,
+    download_history: VecDeque<f32>,
+    upload_history: VecDeque<f32>,
+    addresses: Vec<String>,
+}
+
+/// Addresses assigned to each interface, both ipv4 and ipv6
+fn get_addresses() -> BTreeMap<String, Vec<String>> {
+    let mut addresses: BTreeMap<String, Vec<String>> = BTreeMap::new();
+
+    match getifaddrs() {
+        Ok(ifaddrs) => {
+            for ifaddr in ifaddrs {
+                let address = ifaddr.address.and_then(|address| {
+                    if let Some(address) = address.as_sockaddr_in() {
+                        Some(SocketAddrV4::from(*address).ip().to_string())
+                    } else {
+                        address
+                            .as_sockaddr_in6()
+                            .map(|address| SocketAddrV6::from(*address).ip().to_string())
+                    }
+                });
+
+                if let Some(address) = address {
+                    addresses
+                        .entry(ifaddr.interface_name)
+                        .or_default()
+                        .push(address);
+                }
+            }
+        }
+        Err(e) => warn!("Failed to read the interface addresses: {:?}", e),
+    }
+
+    addresses
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Update(Instant),
+    ToggleMenu,
+}
+
+pub struct NetworkSpeed {
+    networks: Networks,
+    interfaces: BTreeMap<String, InterfaceSpeed>,
+    last_update: Instant,
+}
+
+impl NetworkSpeed {
+    pub fn new() -> Self {
+        Self {
+            networks: Networks::new_with_refreshed_list(),
+            interfaces: BTreeMap::new(),
+            last_update: Instant::now(),
+        }
+    }
+
+    fn is_watched(config: &NetworkSpeedModuleConfig, interface: &str) -> bool {
+        if config.interfaces.is_empty() {
+            interface != "lo"
+        } else {
+            config.interfaces.iter().any(|i| i == interface)
+        }
+    }
+
+    pub fn update(&mut self, message: Message, menu: &mut Menu) -> iced::Command<Message> {
+        match message {
+            Message::Update(now) => {
+                let elapsed = now.duration_since(self.last_update).as_secs_f64();
+                self.last_update = now;
+
+                // interfaces come and go, e.g. vpn tunnels
+                self.networks.refresh_list();
+                let mut addresses = get_addresses();
+
+                self.interfaces
+                    .retain(|name, _| self.networks.iter().any(|(n, _)| n == name));
+
+                if elapsed > 0. {
+                    for (name, data) in self.networks.iter() {
+                        let interface = self.interfaces.entry(name.clone()).or_default();
+
+                        interface.download = data.received() as f64 / elapsed;
+                        interface.upload = data.transmitted() as f64 / elapsed;
+                        interface.addresses = addresses.remove(name).unwrap_or_default();
+
+                        push_sample(
+                            &mut interface.download_history,
+                            interface.download as f32,
+                            HISTORY_SIZE,
+                        );
+                        push_sample(
+                            &mut interface.upload_history,
+                            interface.upload as f32,
+                            HISTORY_SIZE,
+                        );
+                    }
+                }
+
+                iced::Command::none()
+            }
+            Message::ToggleMenu => menu.toggle(MenuType::NetworkSpeed),
+        }
+    }
+
+    pub fn view(&self, config: &NetworkSpeedModuleConfig) -> Option<Element<Message>> {
+        if config.disabled {
+            return None;
+        }
+
+        let (download, upload) = self
+            .interfaces
+            .iter()
+            .filter(|(name, _)| Self::is_watched(config, name))
+            .fold((0., 0.), |(download, upload), (_, interface)| {
+                (download + interface.download, upload + interface.upload)
+            });
+
+        Some(
+            button(
+                column!(
+                    icon(Icons::Download),
+                    text(format_rate(download)).size(10),
+                    icon(Icons::Upload),
+                    text(format_rate(upload)).size(10),
+                )
+                .spacing(2)
+                .align_items(Alignment::Center),
+            )
+            .width(Length::Fill)
+            .padding([4, 0])
+            .style(Button::custom(HeaderButtonStyle::Full))
+            .on_press(Message::ToggleMenu)
+            .into(),
+        )
+    }
+
+    pub fn menu_view(&self, config: &NetworkSpeedModuleConfig) -> Element<Message> {
+        let graph = |label: &str, rate: f64, samples: &VecDeque<f32>| {
+            // graphs are auto scaled, with at least 1 KB/s as upper bound
+            let max = samples.iter().copied().fold(1024., f32::max);
+
+            column!(
+                row!(
+                    text(label.to_string()).size(10).width(Length::Fill),
+                    text(format_rate(rate)).size(10)
+                ),
+                sparkline(samples, max)
+            )
+            .spacing(2)
+        };
+
+        Column::with_children(
+            self.interfaces
+                .iter()
+                .filter(|(name, _)| Self::is_watched(config, name))
+                .enumerate()
+                .flat_map(|(index, (name, interface))| {
+                    [
+                        (index > 0).then(|| horizontal_rule(1).into()),
+                        Some(
+                            column!(
+                                text(name).size(14),
+                                Column::with_children(
+                                    interface
+                                        .addresses
+                                        .iter()
+                                        .map(|address| text(address).size(10).into())
+                                        .collect::<Vec<Element<'_, _, _>>>(),
+                                ),
+                                graph("Download", interface.download, &interface.download_history),
+                                graph("Upload", interface.upload, &interface.upload_history),
+                            )
+                            .spacing(4)
+                            .into(),
+                        ),
+                    ]
+                })
+                .flatten()
+                .collect::<Vec<_>>(),
+        )
+        .spacing(8)
+        .padding(16)
+        .width(350)
+        .into()
+    }
+
+    pub fn subscription(&self, config: &NetworkSpeedModuleConfig) -> iced::Subscription<Message> {
+        iced::time::every(Duration::from_secs(config.interval.max(1))).map(Message::Update)
+    }
+}
@@ -1,21 +1,27 @@
 use iced::{
     theme::Button,
-    widget::{button, column, container, horizontal_rule, row, slider, text, Column, Row},
+    widget::{
+        button, column, container, horizontal_rule, horizontal_space, progress_bar, row, slider,
+        text, Column, Row,
+    },
     Alignment, Command, Element, Length, Subscription, Theme,
 };
 
 use crate::{
-    components::icons::{icon, Icons},
-    config::SettingsModuleConfig,
+    components::icons::{app_icon, icon, Icons},
+    config::{AudioBackend, AudioDevicesConfig, SettingsModuleConfig},
     menu::Menu,
     style::{GhostButtonStyle, SettingsButtonStyle},
     utils::{
-        audio::{AudioCommand, DeviceType, Sink, Sinks, Source, Volume},
+        audio::{
+            default_volume, preferred_device, AppStream, AudioCommand, Card, DeviceType, Sink,
+            Sinks, Source, Volume, MAX_VOLUME, NOISE_SUPPRESSION_SOURCE,
+        },
         Commander,
     },
 };
 
-use super::{Message, SubMenu};
+use super::{quick_setting_button, Message, SubMenu};
 
 #[derive(Debug, Clone)]
 pub enum AudioMessage {
@@ -30,6 +36,28 @@ pub enum AudioMessage {
     DefaultSourceChanged(String, String),
     SinksMore,
     SourcesMore,
+    SinkInputChanges(Vec<AppStream>),
+    SourceOutputChanges(Vec<AppStream>),
+    SinkInputToggleMute(u32),
+    SinkInputVolumeChanged(u32, i32),
+    SinkInputMoved(u32, String),
+    SourceOutputToggleMute(u32),
+    SourceOutputVolumeChanged(u32, i32),
+    SourceOutputMoved(u32, String),
+    ToggleStreamDevices(MixerStream),
+    CardChanges(Vec<Card>),
+    CardProfileChanged(u32, String),
+    SourcePeak(f32),
+    ToggleNoiseSuppression,
+    SinkVolumeStep(i32),
+    SinkBalanceChanged(i32),
+}
+
+/// Application stream shown in the mixer, sink inputs and source outputs have separate indexes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MixerStream {
+    SinkInput(u32),
+    SourceOutput(u32),
 }
 
 pub struct Audio {
@@ -40,6 +68,14 @@ pub struct Audio {
     default_source: String,
     cur_sink_volume: i32,
     cur_source_volume: i32,
+    sink_inputs: Vec<AppStream>,
+    source_outputs: Vec<AppStream>,
+    stream_devices: Option<MixerStream>,
+    cards: Vec<Card>,
+    source_peak: f32,
+    /// Names of the devices seen by the last update, None before the first one
+    known_sinks: Option<Vec<String>>,
+    known_sources: Option<Vec<String>>,
 }
 
 impl Audio {
@@ -52,6 +88,118 @@ impl Audio {
             default_source: String::new(),
             cur_sink_volume: 0,
             cur_source_volume: 0,
+            sink_inputs: Vec::new(),
+            source_outputs: Vec::new(),
+            stream_devices: None,
+            cards: Vec::new(),
+            source_peak: 0.,
+            known_sinks: None,
+            known_sources: None,
+        }
+    }
+
+    /// Applies the device rules when a sink appears or disappears, e.g. a headset is connected
+    fn apply_sink_rules(&mut self, config: &AudioDevicesConfig) {
+        let mut names = self
+            .sinks
+            .iter()
+            .map(|s| s.name.clone())
+            .collect::<Vec<_>>();
+        names.sort();
+        let known = self.known_sinks.replace(names.clone());
+        if known.as_ref() == Some(&names) {
+            return;
+        }
+
+        // default volumes are applied only to new devices, not at startup
+        if let Some(known) = known {
+            for sink in self.sinks.iter_mut().filter(|s| !known.contains(&s.name)) {
+                if let Some(new_volume) = default_volume(&sink.name, &sink.description, config)
+                    .and_then(|volume| sink.volume.scale_volume(volume as f64 / 100.))
+                {
+                    let _ = self
+                        .audio_commander
+                        .send(AudioCommand::SinkVolume(sink.name.clone(), *new_volume));
+                }
+            }
+        }
+
+        if let Some((name, port)) = preferred_device(
+            self.sinks
+                .iter()
+                .map(|s| (s.name.as_str(), s.description.as_str(), s.ports.as_slice())),
+            config,
+            &config.sink_priority,
+        ) {
+            let active = self.default_sink == name
+                && self
+                    .sinks
+                    .iter()
+                    .any(|s| s.name == name && s.ports.iter().any(|p| p.active && p.name == port));
+
+            if !active {
+                let _ = self
+                    .audio_commander
+                    .send(AudioCommand::DefaultSink(name, port));
+            }
+        }
+    }
+
+    /// Applies the device rules when a source appears or disappears,
+    /// the noise suppression filter is not a device and it's never selected by the rules
+    fn apply_source_rules(&mut self, config: &AudioDevicesConfig) {
+        let mut names = self
+            .sources
+            .iter()
+            .filter(|s| s.name != NOISE_SUPPRESSION_SOURCE)
+            .map(|s| s.name.clone())
+            .collect::<Vec<_>>();
+        names.sort();
+        let known = self.known_sources.replace(names.clone());
+        if known.as_ref() == Some(&names) {
+            return;
+        }
+
+        if let Some(known) = known {
+            for source in self
+                .sources
+                .iter_mut()
+                .filter(|s| s.name != NOISE_SUPPRESSION_SOURCE && !known.contains(&s.name))
+            {
+                if let Some(new_volume) = default_volume(&source.name, &source.description, config)
+                    .and_then(|volume| source.volume.scale_volume(volume as f64 / 100.))
+                {
+                    let _ = self
+                        .audio_commander
+                        .send(AudioCommand::SourceVolume(source.name.clone(), *new_volume));
+                }
+            }
+        }
+
+        // the filter is on top of the selected source, switching away would disable it
+        if self.default_source == NOISE_SUPPRESSION_SOURCE {
+            return;
+        }
+
+        if let Some((name, port)) = preferred_device(
+            self.sources
+                .iter()
+                .filter(|s| s.name != NOISE_SUPPRESSION_SOURCE)
+                .map(|s| (s.name.as_str(), s.description.as_str(), s.ports.as_slice())),
+            config,
+            &config.source_priority,
+        ) {
+            let active = self.default_source == name
+                && self
+                    .sources
+                    .iter()
+                    .any(|s| s.name == name && s.ports.iter().any(|p| p.active && p.name == port));
+
+            if !active {
+                let _ = self
+                    .audio_commander
+                    .send(AudioCommand::DefaultSource(name, port));
+            }
         }
     }
 
@@ -64,6 +212,7 @@ impl Audio {
         match message {
             AudioMessage::SinkChanges(sinks) => {
                 self.sinks = sinks;
+                self.apply_sink_rules(&config.audio_devices);
                 self.cur_sink_volume = (self
                     .sinks
                     .iter()
@@ -89,6 +238,7 @@ impl Audio {
             }
             AudioMessage::SourceChanges(sources) => {
                 self.sources = sources;
+                self.apply_source_rules(&config.audio_devices);
                 self.cur_source_volume = (self
                     .sources
                     .iter()
@@ -173,8 +323,30 @@ impl Audio {
 
                 iced::Command::none()
             }
+            AudioMessage::SinkVolumeStep(direction) => {
+                let volume = (self.cur_sink_volume + direction * config.volume_step as i32)
+                    .clamp(0, max_volume(config));
+
+                self.update(AudioMessage::SinkVolumeChanged(volume), menu, config)
+            }
+            AudioMessage::SinkBalanceChanged(balance) => {
+                if let Some(sink) = self
+                    .sinks
+                    .iter_mut()
+                    .find(|sink| sink.name == self.default_sink)
+                {
+                    if let Some(new_volume) = sink.volume.set_stereo_balance(balance as f64 / 100.)
+                    {
+                        let _ = self
+                            .audio_commander
+                            .send(AudioCommand::SinkVolume(sink.name.clone(), *new_volume));
+                    }
+                }
+
+                iced::Command::none()
+            }
             AudioMessage::SinkVolumeChanged(volume) => {
-                self.cur_sink_volume = volume;
+                self.cur_sink_volume = volume.min(max_volume(config));
                 if let Some(sink) = self
                     .sinks
                     .iter_mut()
@@ -268,9 +440,173 @@ impl Audio {
                     iced::Command::none()
                 }
             }
+            AudioMessage::SinkInputChanges(sink_inputs) => {
+                self.sink_inputs = sink_inputs;
+
+                iced::Command::none()
+            }
+            AudioMessage::SourceOutputChanges(source_outputs) => {
+                self.source_outputs = source_outputs;
+
+                iced::Command::none()
+            }
+            AudioMessage::SinkInputToggleMute(index) => {
+                if let Some(sink_input) = self.sink_inputs.iter().find(|s| s.index == index) {
+                    let _ = self
+                        .audio_commander
+                        .send(AudioCommand::SinkInputMute(index, !sink_input.is_mute));
+                }
+
+                iced::Command::none()
+            }
+            AudioMessage::SinkInputVolumeChanged(index, volume) => {
+                if let Some(sink_input) = self.sink_inputs.iter_mut().find(|s| s.index == index) {
+                    if let Some(new_volume) = sink_input.volume.scale_volume(volume as f64 / 100.) {
+                        let _ = self
+                            .audio_commander
+                            .send(AudioCommand::SinkInputVolume(index, *new_volume));
+                    }
+                }
+
+                iced::Command::none()
+            }
+            AudioMessage::SinkInputMoved(index, sink) => {
+                self.stream_devices = None;
+                let _ = self
+                    .audio_commander
+                    .send(AudioCommand::MoveSinkInput(index, sink));
+
+                iced::Command::none()
+            }
+            AudioMessage::SourceOutputToggleMute(index) => {
+                if let Some(source_output) = self.source_outputs.iter().find(|s| s.index == index) {
+                    let _ = self.audio_commander.send(AudioCommand::SourceOutputMute(
+                        index,
+                        !source_output.is_mute,
+                    ));
+                }
+
+                iced::Command::none()
+            }
+            AudioMessage::SourceOutputVolumeChanged(index, volume) => {
+                if let Some(source_output) =
+                    self.source_outputs.iter_mut().find(|s| s.index == index)
+                {
+                    if let Some(new_volume) =
+                        source_output.volume.scale_volume(volume as f64 / 100.)
+                    {
+                        let _ = self
+                            .audio_commander
+                            .send(AudioCommand::SourceOutputVolume(index, *new_volume));
+                    }
+                }
+
+                iced::Command::none()
+            }
+            AudioMessage::SourceOutputMoved(index, source) => {
+                self.stream_devices = None;
+                let _ = self
+                    .audio_commander
+                    .send(AudioCommand::MoveSourceOutput(index, source));
+
+                iced::Command::none()
+            }
+            AudioMessage::CardChanges(cards) => {
+                self.cards = cards;
+
+                iced::Command::none()
+            }
+            AudioMessage::CardProfileChanged(index, profile) => {
+                if let Some(card) = self.cards.iter_mut().find(|c| c.index == index) {
+                    card.active_profile = Some(profile.clone());
+                }
+
+                let _ = self
+                    .audio_commander
+                    .send(AudioCommand::CardProfile(index, profile));
+
+                iced::Command::none()
+            }
+            AudioMessage::SourcePeak(peak) => {
+                self.source_peak = peak;
+
+                iced::Command::none()
+            }
+            AudioMessage::ToggleNoiseSuppression => {
+                let _ = self.audio_commander.send(if self.noise_suppression() {
+                    AudioCommand::NoiseSuppression(false, String::new())
+                } else {
+                    AudioCommand::NoiseSuppression(true, self.default_source.clone())
+                });
+
+                iced::Command::none()
+            }
+            AudioMessage::ToggleStreamDevices(stream) => {
+                if self.stream_devices == Some(stream) {
+                    self.stream_devices = None;
+                } else {
+                    self.stream_devices = Some(stream);
+                }
+
+                iced::Command::none()
+            }
         }
     }
 
+    /// Name, volume and mute state of the default sink, None until it's known
+    pub fn sink_level(&self) -> Option<(String, i32, bool)> {
+        self.sinks
+            .iter()
+            .find(|s| s.name == self.default_sink && s.ports.iter().any(|p| p.active))
+            .map(|s| {
+                (
+                    s.name.clone(),
+                    (s.volume.get_volume() * 100.).round() as i32,
+                    s.is_mute,
+                )
+            })
+    }
+
+    /// Name, volume and mute state of the default source, None until it's known
+    pub fn source_level(&self) -> Option<(String, i32, bool)> {
+        self.sources
+            .iter()
+            .find(|s| s.name == self.default_source && s.ports.iter().any(|p| p.active))
+            .map(|s| {
+                (
+                    s.name.clone(),
+                    (s.volume.get_volume() * 100.).round() as i32,
+                    s.is_mute,
+                )
+            })
+    }
+
+    fn noise_suppression(&self) -> bool {
+        self.sources
+            .iter()
+            .any(|s| s.name == NOISE_SUPPRESSION_SOURCE)
+    }
+
+    pub fn get_noise_suppression_quick_setting_button(
+        &self,
+    ) -> Option<(Element<Message>, Option<Element<Message>>)> {
+        if self.sources.is_empty() {
+            return None;
+        }
+
+        Some((
+            quick_setting_button(
+                Icons::Mic1,
+                "Noise Suppression".to_owned(),
+                None,
+                self.noise_suppression(),
+                Message::Audio(AudioMessage::ToggleNoiseSuppression),
+                None,
+            ),
+            None,
+        ))
+    }
+
     pub fn sink_indicator<'a, Message>(&self) -> Option<Element<'a, Message>> {
         if !self.sinks.is_empty() {
             let icon_type = self.sinks.get_icon();
@@ -284,6 +620,7 @@ impl Audio {
     pub fn audio_sliders<'a>(
         &self,
         sub_menu: Option<SubMenu>,
+        max_volume: i32,
     ) -> (Option<Element<'a, Message>>, Option<Element<'a, Message>>) {
         let active_sink = self
             .sinks
@@ -296,8 +633,11 @@ impl Audio {
                 s.is_mute,
                 Message::Audio(AudioMessage::SinkToggleMute),
                 self.cur_sink_volume,
+                max_volume,
                 |v| Message::Audio(AudioMessage::SinkVolumeChanged(v)),
-                if self.sinks.iter().map(|s| s.ports.len()).sum::<usize>() > 1 {
+                if self.sinks.iter().map(|s| s.ports.len()).sum::<usize>() > 1
+                    || !self.sink_cards().is_empty()
+                {
                     Some((sub_menu, Message::ToggleSubMenu(SubMenu::Sinks)))
                 } else {
                     None
@@ -311,25 +651,59 @@ impl Audio {
             .find(|source| source.ports.iter().any(|p| p.active));
 
         let source_slider = active_source.map(|s| {
-            audio_slider(
+            let volume_slider = audio_slider(
                 SliderType::Source,
                 s.is_mute,
                 Message::Audio(AudioMessage::SourceToggleMute),
                 self.cur_source_volume,
+                100,
                 |v| Message::Audio(AudioMessage::SourceVolumeChanged(v)),
-                if self.sources.iter().map(|s| s.ports.len()).sum::<usize>() > 1 {
+                if self.sources.iter().map(|s| s.ports.len()).sum::<usize>() > 1
+                    || !self.source_cards().is_empty()
+                {
                     Some((sub_menu, Message::ToggleSubMenu(SubMenu::Sources)))
                 } else {
                     None
                 },
+            );
+
+            // live input level of the source
+            column!(
+                volume_slider,
+                container(progress_bar(0.0..=1.0, self.source_peak).height(Length::Fixed(3.)))
+                    .padding([0, 8]),
             )
+            .spacing(4)
+            .into()
         });
 
         (sink_slider, source_slider)
     }
 
+    /// Cards with a choice of profiles that provide the sinks, cards without any
+    /// sink or source are listed too, e.g. a bluetooth headset with the "off" profile
+    fn sink_cards(&self) -> Vec<&Card> {
+        self.cards
+            .iter()
+            .filter(|c| {
+                c.profiles.len() > 1
+                    && (self.sinks.iter().any(|s| s.card == Some(c.index))
+                        || !self.sources.iter().any(|s| s.card == Some(c.index)))
+            })
+            .collect()
+    }
+
+    fn source_cards(&self) -> Vec<&Card> {
+        self.cards
+            .iter()
+            .filter(|c| {
+                c.profiles.len() > 1 && self.sources.iter().any(|s| s.card == Some(c.index))
+            })
+            .collect()
+    }
+
     pub fn sinks_submenu<'a>(&self, show_more: bool) -> Element<'a, Message> {
-        audio_submenu(
+        let sinks = audio_submenu(
             self.sinks
                 .iter()
                 .flat_map(|s| {
@@ -349,11 +723,42 @@ impl Audio {
             } else {
                 None
             },
-        )
+        );
+
+        let sinks = with_card_profiles(sinks, self.sink_cards());
+
+        // stereo sinks only, the balance of other channel maps is not supported
+        let balance = self
+            .sinks
+            .iter()
+            .find(|s| s.name == self.default_sink)
+            .and_then(|s| s.volume.stereo_balance());
+
+        if let Some(balance) = balance {
+            column!(
+                row!(
+                    text("L").size(12),
+                    slider(-100..=100, (balance * 100.).round() as i32, |v| {
+                        Message::Audio(AudioMessage::SinkBalanceChanged(v))
+                    })
+                    .step(1)
+                    .width(Length::Fill),
+                    text("R").size(12),
+                )
+                .spacing(8)
+                .padding([0, 8])
+                .align_items(Alignment::Center),
+                sinks,
+            )
+            .spacing(8)
+            .into()
+        } else {
+            sinks
+        }
     }
 
     pub fn sources_submenu<'a>(&self, show_more: bool) -> Element<'a, Message> {
-        audio_submenu(
+        let sources = audio_submenu(
             self.sources
                 .iter()
                 .flat_map(|s| {
@@ -373,14 +778,87 @@ impl Audio {
             } else {
                 None
             },
+        );
+
+        with_card_profiles(sources, self.source_cards())
+    }
+
+    pub fn mixer_button<'a>(&self, sub_menu: Option<SubMenu>) -> Option<Element<'a, Message>> {
+        let count = self.sink_inputs.len() + self.source_outputs.len();
+
+        if count == 0 {
+            return None;
+        }
+
+        Some(
+            button(
+                row!(
+                    icon(Icons::Mixer),
+                    text(format!("Applications ({})", count)).size(12),
+                    horizontal_space(Length::Fill),
+                    icon(if sub_menu == Some(SubMenu::Mixer) {
+                        Icons::Close
+                    } else {
+                        Icons::RightArrow
+                    }),
+                )
+                .spacing(16)
+                .align_items(Alignment::Center),
+            )
+            .padding([8, 13])
+            .width(Length::Fill)
+            .on_press(Message::ToggleSubMenu(SubMenu::Mixer))
+            .style(Button::custom(SettingsButtonStyle))
+            .into(),
         )
     }
 
-    pub fn subscription(&self) -> Subscription<AudioMessage> {
-        crate::utils::audio::subscription(self.audio_commander.give_receiver())
+    pub fn mixer_submenu<'a>(&self) -> Element<'a, Message> {
+        let sinks = self
+            .sinks
+            .iter()
+            .map(|s| (s.index, s.name.clone(), s.description.clone()))
+            .collect::<Vec<_>>();
+        let sources = self
+            .sources
+            .iter()
+            .map(|s| (s.index, s.name.clone(), s.description.clone()))
+            .collect::<Vec<_>>();
+
+        let sink_inputs = self.sink_inputs.iter().map(|s| {
+            let stream = MixerStream::SinkInput(s.index);
+
+            mixer_entry(s, stream, &sinks, self.stream_devices == Some(stream))
+        });
+        let source_outputs = self.source_outputs.iter().map(|s| {
+            let stream = MixerStream::SourceOutput(s.index);
+
+            mixer_entry(s, stream, &sources, self.stream_devices == Some(stream))
+        });
+
+        Column::with_children(sink_inputs.chain(source_outputs).collect::<Vec<_>>())
+            .spacing(12)
+            .into()
+    }
+
+    pub fn subscription(&self, backend: AudioBackend) -> Subscription<AudioMessage> {
+        crate::utils::audio::subscription(backend, self.audio_commander.give_receiver())
+    }
+
+    pub fn source_meter_subscription(&self) -> Subscription<AudioMessage> {
+        if self.default_source.is_empty() {
+            Subscription::none()
+        } else {
+            crate::utils::audio::source_meter(self.default_source.clone())
+        }
     }
 }
 
+/// Max sink volume percentage, over-amplification is capped to [`MAX_VOLUME`]
+pub fn max_volume(config: &SettingsModuleConfig) -> i32 {
+    config.max_volume.min(MAX_VOLUME) as i32
+}
+
 pub enum SliderType {
     Sink,
     Source,
@@ -391,6 +869,7 @@ pub fn audio_slider<'a, Message: 'a + Clone>(
     is_mute: bool,
     toggle_mute: Message,
     volume: i32,
+    max_volume: i32,
     volume_changed: impl Fn(i32) -> Message + 'a,
     with_submenu: Option<(Option<SubMenu>, Message)>,
 ) -> Element<'a, Message> {
@@ -420,7 +899,7 @@ pub fn audio_slider<'a, Message: 'a + Clone>(
                 .into(),
             ),
             Some(
-                slider(0..=100, volume, volume_changed)
+                slider(0..=max_volume, volume, volume_changed)
                     .step(1)
                     .width(Length::Fill)
                     // .style(SliderStyle)
@@ -447,6 +926,162 @@ pub fn audio_slider<'a, Message: 'a + Clone>(
     .into()
 }
 
+/// Appends the profile pickers of the cards below a devices submenu
+fn with_card_profiles<'a>(
+    devices: Element<'a, Message>,
+    cards: Vec<&Card>,
+) -> Element<'a, Message> {
+    if cards.is_empty() {
+        return devices;
+    }
+
+    let profiles = cards.into_iter().map(|card| {
+        column!(
+            text(card.description.clone()).size(12),
+            Column::with_children(
+                card.profiles
+                    .iter()
+                    .map(|profile| {
+                        if card.active_profile.as_ref() == Some(&profile.name) {
+                            container(text(profile.description.clone()).size(12))
+                                .padding([4, 12])
+                                .style(|theme: &Theme| container::Appearance {
+                                    text_color: Some(theme.palette().success),
+                                    ..Default::default()
+                                })
+                                .into()
+                        } else {
+                            button(text(profile.description.clone()).size(12))
+                                .on_press(Message::Audio(AudioMessage::CardProfileChanged(
+                                    card.index,
+                                    profile.name.clone(),
+                                )))
+                                .padding([4, 12])
+                                .width(Length::Fill)
+                                .style(Button::custom(GhostButtonStyle))
+                                .into()
+                        }
+                    })
+                    .collect::<Vec<_>>(),
+            )
+            .spacing(4),
+        )
+        .spacing(8)
+        .into()
+    });
+
+    Column::with_children(
+        std::iter::once(devices)
+            .chain(profiles)
+            .flat_map(|element| [horizontal_rule(1).into(), element])
+            .skip(1)
+            .collect::<Vec<_>>(),
+    )
+    .spacing(12)
+    .into()
+}
+
+/// Mixer row of an application stream, the devices are (index, name, description)
+fn mixer_entry<'a>(
+    app_stream: &AppStream,
+    stream: MixerStream,
+    devices: &[(u32, String, String)],
+    show_devices: bool,
+) -> Element<'a, Message> {
+    let device = devices
+        .iter()
+        .find(|(index, _, _)| *index == app_stream.device)
+        .map(|(_, _, description)| description.clone())
+        .unwrap_or_default();
+    let volume = if app_stream.is_mute {
+        0
+    } else {
+        (app_stream.volume.get_volume() * 100.) as i32
+    };
+
+    let active = app_stream.active;
+    let header = row!(
+        // paused or idle streams are dimmed
+        container(
+            row!(
+                icon(app_icon(
+                    app_stream.icon_name.as_deref().unwrap_or(&app_stream.name)
+                )),
+                text(app_stream.name.clone()).size(12),
+            )
+            .spacing(8)
+            .align_items(Alignment::Center),
+        )
+        .width(Length::Fill)
+        .style(move |theme: &Theme| container::Appearance {
+            text_color: if active {
+                None
+            } else {
+                Some(theme.extended_palette().background.strong.color)
+            },
+            ..Default::default()
+        }),
+        button(text(device).size(10))
+            .padding([2, 8])
+            .on_press(Message::Audio(AudioMessage::ToggleStreamDevices(stream)))
+            .style(Button::custom(GhostButtonStyle)),
+    )
+    .spacing(8)
+    .align_items(Alignment::Center);
+
+    let slider = match stream {
+        MixerStream::SinkInput(index) => audio_slider(
+            SliderType::Sink,
+            app_stream.is_mute,
+            Message::Audio(AudioMessage::SinkInputToggleMute(index)),
+            volume,
+            100,
+            move |v| Message::Audio(AudioMessage::SinkInputVolumeChanged(index, v)),
+            None,
+        ),
+        MixerStream::SourceOutput(index) => audio_slider(
+            SliderType::Source,
+            app_stream.is_mute,
+            Message::Audio(AudioMessage::SourceOutputToggleMute(index)),
+            volume,
+            100,
+            move |v| Message::Audio(AudioMessage::SourceOutputVolumeChanged(index, v)),
+            None,
+        ),
+    };
+
+    let mut entry = column!(header, slider).spacing(4);
+
+    if show_devices {
+        entry = entry.push(
+            Column::with_children(
+                devices
+                    .iter()
+                    .filter(|(index, _, _)| *index != app_stream.device)
+                    .map(|(_, name, description)| {
+                        button(text(description.clone()).size(12))
+                            .padding([4, 12])
+                            .width(Length::Fill)
+                            .on_press(Message::Audio(match stream {
+                                MixerStream::SinkInput(index) => {
+                                    AudioMessage::SinkInputMoved(index, name.clone())
+                                }
+                                MixerStream::SourceOutput(index) => {
+                                    AudioMessage::SourceOutputMoved(index, name.clone())
+                                }
+                            }))
+                            .style(Button::custom(GhostButtonStyle))
+                            .into()
+                    })
+                    .collect::<Vec<_>>(),
+            )
+            .spacing(4),
+        );
+    }
+
+    entry.into()
+}
+
 pub struct SubmenuEntry<Message> {
     pub name: String,
     pub device: DeviceType,
@@ -2,16 +2,17 @@ use super::{quick_setting_button, sub_menu_wrapper, Message, SubMenu};
 use crate::{
     components::icons::{icon, Icons},
     config::SettingsModuleConfig,
-    menu::Menu,
-    style::GhostButtonStyle,
+    menu::{Menu, MenuType},
+    style::{GhostButtonStyle, QuickSettingsButtonStyle, SettingsButtonStyle},
     utils::{bluetooth::BluetoothCommand, Commander},
 };
 use iced::{
     theme::Button,
-    widget::{button, column, container, horizontal_rule, row, text, Column, Row},
+    widget::{button, column, container, horizontal_rule, row, scrollable, text, Column, Row},
     Element, Length, Theme,
 };
-use log::debug;
+use log::{debug, warn};
+use std::collections::HashMap;
 
 #[derive(PartialEq, Eq, Debug, Clone)]
 pub enum BluetoothState {
@@ -20,24 +21,89 @@ pub enum BluetoothState {
     Inactive,
 }
 
+#[derive(Debug, Clone)]
+pub struct Adapter {
+    pub path: String,
+    pub name: String,
+}
+
 #[derive(Debug, Clone)]
 pub struct Device {
+    pub path: String,
     pub name: String,
     pub battery: Option<u8>,
+    pub connected: bool,
+    pub paired: bool,
+    pub trusted: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeviceAction {
+    Pair,
+    Connect,
+    Disconnect,
+    Trust,
+    Remove,
+}
+
+/// A request of the pairing agent
+#[derive(Debug, Clone)]
+pub struct AgentRequest {
+    pub path: String,
+    pub device: String,
+    pub kind: AgentRequestKind,
+}
+
+#[derive(Debug, Clone)]
+pub enum AgentRequestKind {
+    PinCode,
+    Passkey,
+    DisplayPinCode(String),
+    DisplayPasskey(u32),
+    Confirmation(u32),
+    Authorization,
+    AuthorizeService(String),
+}
+
+impl AgentRequestKind {
+    pub fn needs_input(&self) -> bool {
+        matches!(self, AgentRequestKind::PinCode | AgentRequestKind::Passkey)
+    }
+
+    /// Display requests only show a code, bluez doesn't wait for an answer
+    pub fn expects_reply(&self) -> bool {
+        !matches!(
+            self,
+            AgentRequestKind::DisplayPinCode(_) | AgentRequestKind::DisplayPasskey(_)
+        )
+    }
 }
 
 #[derive(Debug, Clone)]
 pub enum BluetoothMessage {
     Status(BluetoothState),
+    Adapters(Vec<Adapter>, Option<String>),
+    SelectAdapter(String),
     DeviceList(Vec<Device>),
+    Discovering(bool),
     Toggle,
+    Scan,
+    Action(String, DeviceAction),
+    ActionFinished(String, Option<String>),
+    AgentRequest(AgentRequest),
+    AgentCancelled,
     More,
 }
 
 pub struct Bluetooth {
     commander: Commander<BluetoothCommand>,
     state: BluetoothState,
+    adapters: Vec<Adapter>,
+    selected_adapter: Option<String>,
     devices: Vec<Device>,
+    discovering: bool,
+    pending: HashMap<String, DeviceAction>,
+    errors: HashMap<String, String>,
 }
 
 impl Bluetooth {
@@ -45,7 +111,12 @@ impl Bluetooth {
         Self {
             commander: Commander::new(),
             state: BluetoothState::Unavailable,
+            adapters: Vec::new(),
+            selected_adapter: None,
             devices: Vec::new(),
+            discovering: false,
+            pending: HashMap::new(),
+            errors: HashMap::new(),
         }
     }
 
@@ -54,6 +125,7 @@ impl Bluetooth {
         msg: BluetoothMessage,
         menu: &mut Menu,
         sub_menu: &mut Option<SubMenu>,
+        agent_dialog: &mut Option<(AgentRequest, String)>,
         config: &SettingsModuleConfig,
     ) -> iced::Command<Message> {
         match msg {
@@ -61,22 +133,109 @@ impl Bluetooth {
                 debug!("Bluetooth state: {:?}", state);
                 self.state = state;
 
-                if self.state != BluetoothState::Active && *sub_menu == Some(SubMenu::Bluetooth) {
+                if !self.has_sub_menu() && *sub_menu == Some(SubMenu::Bluetooth) {
                     *sub_menu = None;
                 }
 
                 iced::Command::none()
             }
+            BluetoothMessage::Adapters(adapters, selected) => {
+                self.adapters = adapters;
+                self.selected_adapter = selected;
+
+                iced::Command::none()
+            }
+            BluetoothMessage::SelectAdapter(path) => {
+                if self.selected_adapter.as_ref() != Some(&path) {
+                    self.discovering = false;
+                    let _ = self.commander.send(BluetoothCommand::SelectAdapter(path));
+                }
+
+                iced::Command::none()
+            }
             BluetoothMessage::DeviceList(devices) => {
+                // discovered devices disappear after a while, drop their state
+                self.pending
+                    .retain(|path, _| devices.iter().any(|d| d.path == *path));
+                self.errors
+                    .retain(|path, _| devices.iter().any(|d| d.path == *path));
                 self.devices = devices;
 
                 iced::Command::none()
             }
+            BluetoothMessage::Discovering(discovering) => {
+                self.discovering = discovering;
+
+                iced::Command::none()
+            }
             BluetoothMessage::Toggle => {
                 let _ = self.commander.send(BluetoothCommand::TogglePower);
 
                 iced::Command::none()
             }
+            BluetoothMessage::Scan => {
+                self.start_discovery();
+
+                iced::Command::none()
+            }
+            BluetoothMessage::Action(path, action) => {
+                let command = match action {
+                    DeviceAction::Pair => BluetoothCommand::Pair(path.clone()),
+                    DeviceAction::Connect => BluetoothCommand::Connect(path.clone()),
+                    DeviceAction::Disconnect => BluetoothCommand::Disconnect(path.clone()),
+                    DeviceAction::Trust => BluetoothCommand::Trust(
+                        path.clone(),
+                        !self.devices.iter().any(|d| d.path == path && d.trusted),
+                    ),
+                    DeviceAction::Remove => BluetoothCommand::Remove(path.clone()),
+                };
+
+                self.errors.remove(&path);
+                if self.commander.send(command).is_ok() {
+                    self.pending.insert(path, action);
+                }
+
+                iced::Command::none()
+            }
+            BluetoothMessage::ActionFinished(path, error) => {
+                self.pending.remove(&path);
+                if let Some(error) = error {
+                    warn!("Bluetooth device {} action failed: {}", path, error);
+                    self.errors.insert(path.clone(), error);
+                }
+
+                if agent_dialog.as_ref().is_some_and(|(r, _)| r.path == path) {
+                    *agent_dialog = None;
+                    menu.unset_keyboard_interactivity()
+                } else {
+                    iced::Command::none()
+                }
+            }
+            BluetoothMessage::AgentRequest(request) => {
+                debug!("Bluetooth agent request: {:?}", request);
+                let needs_input = request.kind.needs_input();
+                *agent_dialog = Some((request, String::new()));
+
+                // requests started from the other device must be visible too
+                let open_menu = if menu.get_menu_type() != Some(MenuType::Settings) {
+                    menu.toggle(MenuType::Settings)
+                } else {
+                    iced::Command::none()
+                };
+
+                if needs_input {
+                    iced::Command::batch(vec![open_menu, menu.set_keyboard_interactivity()])
+                } else {
+                    open_menu
+                }
+            }
+            BluetoothMessage::AgentCancelled => {
+                if agent_dialog.take().is_some() {
+                    menu.unset_keyboard_interactivity()
+                } else {
+                    iced::Command::none()
+                }
+            }
             BluetoothMessage::More => {
                 if let Some(cmd) = &config.bluetooth_more_cmd {
                     crate::utils::launcher::execute_command(cmd.to_string());
@@ -88,16 +247,32 @@ impl Bluetooth {
         }
     }
 
+    pub fn agent_reply(&self, reply: Option<String>) {
+        let _ = self.commander.send(BluetoothCommand::AgentReply(reply));
+    }
+
+    pub fn start_discovery(&mut self) {
+        if self.state == BluetoothState::Active && !self.discovering {
+            let _ = self.commander.send(BluetoothCommand::StartDiscovery);
+        }
+    }
+
     pub fn get_quick_setting_button(
         &self,
         sub_menu: Option<SubMenu>,
         show_more_button: bool,
     ) -> Option<(Element<Message>, Option<Element<Message>>)> {
+        if self.state == BluetoothState::Unavailable {
+            return None;
+        }
+
         Some((
             quick_setting_button(
                 Icons::Bluetooth,
                 "Bluetooth".to_owned(),
-                None,
+                self.selected_adapter()
+                    .filter(|_| self.adapters.len() > 1)
+                    .map(|adapter| adapter.name.clone()),
                 self.state == BluetoothState::Active,
                 Message::Bluetooth(BluetoothMessage::Toggle),
                 Some((
@@ -105,7 +280,7 @@ impl Bluetooth {
                     sub_menu,
                     Message::ToggleSubMenu(SubMenu::Bluetooth),
                 ))
-                .filter(|_| self.state == BluetoothState::Active),
+                .filter(|_| self.has_sub_menu()),
             ),
             sub_menu
                 .filter(|menu_type| *menu_type == SubMenu::Bluetooth)
@@ -113,29 +288,107 @@ impl Bluetooth {
         ))
     }
 
-    pub fn bluetooth_menu(&self, show_more_button: bool) -> Element<Message> {
-        let main = if self.devices.is_empty() {
-            text("No devices connected").into()
+    fn selected_adapter(&self) -> Option<&Adapter> {
+        self.adapters
+            .iter()
+            .find(|a| self.selected_adapter.as_ref() == Some(&a.path))
+    }
+
+    /// The adapter selector must be reachable even if the current adapter is off
+    fn has_sub_menu(&self) -> bool {
+        self.state == BluetoothState::Active || self.adapters.len() > 1
+    }
+
+    fn adapter_selector(&self) -> Option<Element<Message>> {
+        if self.adapters.len() > 1 {
+            Some(
+                Row::with_children(
+                    self.adapters
+                        .iter()
+                        .map(|adapter| {
+                            button(text(adapter.name.to_string()).size(10))
+                                .padding([2, 8])
+                                .style(Button::custom(QuickSettingsButtonStyle(
+                                    self.selected_adapter.as_ref() == Some(&adapter.path),
+                                )))
+                                .on_press(Message::Bluetooth(BluetoothMessage::SelectAdapter(
+                                    adapter.path.clone(),
+                                )))
+                                .into()
+                        })
+                        .collect::<Vec<Element<Message>>>(),
+                )
+                .spacing(4)
+                .into(),
+            )
         } else {
-            Column::with_children(
-                self.devices
-                    .iter()
-                    .map(|d| {
-                        Row::with_children(
-                            vec![
-                                Some(text(d.name.to_string()).width(iced::Length::Fill).into()),
-                                d.battery.map(Self::battery_level),
-                            ]
-                            .into_iter()
-                            .flatten()
-                            .collect::<Vec<_>>(),
-                        )
-                        .into()
-                    })
-                    .collect::<Vec<Element<Message>>>(),
+            None
+        }
+    }
+
+    pub fn bluetooth_menu(&self, show_more_button: bool) -> Element<Message> {
+        if self.state != BluetoothState::Active {
+            return Column::with_children(
+                vec![
+                    self.adapter_selector(),
+                    Some(text("The adapter is turned off").into()),
+                ]
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>(),
             )
             .spacing(8)
-            .into()
+            .into();
+        }
+
+        let paired = self
+            .devices
+            .iter()
+            .filter(|d| d.paired)
+            .map(|d| self.device_entry(d))
+            .collect::<Vec<_>>();
+        let available = self
+            .devices
+            .iter()
+            .filter(|d| !d.paired)
+            .map(|d| self.device_entry(d))
+            .collect::<Vec<_>>();
+
+        let main = column!(
+            if paired.is_empty() {
+                Element::from(text("No paired devices"))
+            } else {
+                Column::with_children(paired).spacing(8).into()
+            },
+            horizontal_rule(1),
+            row!(
+                text("Available devices").width(Length::Fill),
+                text(if self.discovering { "Scanning..." } else { "" }).size(12),
+                button(icon(Icons::Refresh))
+                    .padding([4, 10])
+                    .style(Button::custom(SettingsButtonStyle))
+                    .on_press_maybe(if self.discovering {
+                        None
+                    } else {
+                        Some(Message::Bluetooth(BluetoothMessage::Scan))
+                    }),
+            )
+            .spacing(8)
+            .width(Length::Fill)
+            .align_items(iced::Alignment::Center),
+            container(scrollable(
+                Column::with_children(available)
+                    .spacing(8)
+                    .padding([0, 8, 0, 0])
+            ))
+            .max_height(200),
+        )
+        .spacing(8);
+
+        let main = if let Some(adapter_selector) = self.adapter_selector() {
+            column!(adapter_selector, horizontal_rule(1), main).spacing(8)
+        } else {
+            main
         };
 
         if show_more_button {
@@ -151,10 +404,125 @@ impl Bluetooth {
             .spacing(12)
             .into()
         } else {
-            main
+            main.into()
         }
     }
 
+    fn device_entry<'a>(&'a self, device: &'a Device) -> Element<'a, Message> {
+        let connected = device.connected;
+
+        let status: Option<Element<'a, Message>> =
+            if let Some(action) = self.pending.get(&device.path) {
+                Some(
+                    text(match action {
+                        DeviceAction::Pair => "Pairing...",
+                        DeviceAction::Connect => "Connecting...",
+                        DeviceAction::Disconnect => "Disconnecting...",
+                        DeviceAction::Trust => "Updating...",
+                        DeviceAction::Remove => "Removing...",
+                    })
+                    .size(10)
+                    .into(),
+                )
+            } else if let Some(error) = self.errors.get(&device.path) {
+                Some(
+                    container(text(error).size(10))
+                        .style(|theme: &Theme| iced::widget::container::Appearance {
+                            text_color: Some(theme.palette().danger),
+                            ..iced::widget::container::Appearance::default()
+                        })
+                        .into(),
+                )
+            } else {
+                None
+            };
+
+        let actions = if self.pending.contains_key(&device.path) {
+            None
+        } else if device.paired {
+            Some(
+                row!(
+                    Self::action_button(
+                        if device.connected {
+                            "Disconnect"
+                        } else {
+                            "Connect"
+                        },
+                        &device.path,
+                        if device.connected {
+                            DeviceAction::Disconnect
+                        } else {
+                            DeviceAction::Connect
+                        }
+                    ),
+                    Self::action_button(
+                        if device.trusted { "Untrust" } else { "Trust" },
+                        &device.path,
+                        DeviceAction::Trust
+                    ),
+                    Self::action_button("Forget", &device.path, DeviceAction::Remove),
+                )
+                .spacing(4)
+                .into(),
+            )
+        } else {
+            Some(Self::action_button(
+                "Pair",
+                &device.path,
+                DeviceAction::Pair,
+            ))
+        };
+
+        Column::with_children(
+            vec![
+                Some(
+                    Row::with_children(
+                        vec![
+                            Some(
+                                container(text(device.name.to_string()))
+                                    .style(move |theme: &Theme| {
+                                        iced::widget::container::Appearance {
+                                            text_color: if connected {
+                                                Some(theme.palette().success)
+                                            } else {
+                                                None
+                                            },
+                                            ..iced::widget::container::Appearance::default()
+                                        }
+                                    })
+                                    .width(Length::Fill)
+                                    .into(),
+                            ),
+                            device.battery.map(Self::battery_level),
+                        ]
+                        .into_iter()
+                        .flatten()
+                        .collect::<Vec<_>>(),
+                    )
+                    .into(),
+                ),
+                actions,
+                status,
+            ]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>(),
+        )
+        .spacing(4)
+        .into()
+    }
+
+    fn action_button<'a>(label: &'a str, path: &str, action: DeviceAction) -> Element<'a, Message> {
+        button(text(label).size(10))
+            .padding([2, 8])
+            .style(Button::custom(SettingsButtonStyle))
+            .on_press(Message::Bluetooth(BluetoothMessage::Action(
+                path.to_string(),
+                action,
+            )))
+            .into()
+    }
+
     fn battery_level<'a>(battery: u8) -> Element<'a, Message> {
         container(
             row!(
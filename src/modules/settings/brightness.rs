@@ -17,22 +17,24 @@ pub enum BrightnessMessage {
 
 pub struct Brightness {
     commander: Commander<f64>,
-    value: i32,
+    value: Option<i32>,
 }
 
 impl Brightness {
     pub fn new() -> Self {
         Self {
             commander: Commander::new(),
-            value: 0,
+            value: None,
         }
     }
 
     pub fn update<Message>(&mut self, msg: BrightnessMessage) -> Command<Message> {
         match msg {
             BrightnessMessage::Changed(value, externa_source) => {
-                if (value - (self.value as f64 / 100.)).abs() > 0.01 {
-                    self.value = (value * 100.).round() as i32;
+                if self.value.map_or(true, |current| {
+                    (value - (current as f64 / 100.)).abs() > 0.01
+                }) {
+                    self.value = Some((value * 100.).round() as i32);
                     if !externa_source {
                         self.commander.send(value).unwrap();
                     }
@@ -42,12 +44,17 @@ impl Brightness {
         }
     }
 
+    /// Brightness percentage, None until it's read from the device
+    pub fn level(&self) -> Option<i32> {
+        self.value
+    }
+
     pub fn brightness_slider<'a>(&self) -> Element<'a, Message> {
         row!(
             container(icon(Icons::Brightness)).padding([8, 11]),
-            slider(0..=100, self.value, |v| Message::Brightness(
-                BrightnessMessage::Changed(v as f64 / 100., false)
-            ))
+            slider(0..=100, self.value.unwrap_or_default(), |v| {
+                Message::Brightness(BrightnessMessage::Changed(v as f64 / 100., false))
+            })
             .step(1)
             .width(Length::Fill),
         )
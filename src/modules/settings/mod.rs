@@ -1,16 +1,21 @@
 use self::{
     audio::{Audio, AudioMessage},
     battery::{battery_indicator, settings_battery_indicator},
-    bluetooth::BluetoothMessage,
+    bluetooth::{AgentRequest, BluetoothMessage},
     net::NetMessage,
     power::PowerMessage,
     powerprofiles::{PowerProfiles, PowerProfilesMessage},
 };
 use crate::{
-    components::icons::{icon, Icons},
+    bluetooth_dialog,
+    components::{
+        icons::{icon, Icons},
+        scroll_area::scroll_area,
+    },
     config::SettingsModuleConfig,
     menu::{Menu, MenuType},
     modules::settings::power::power_menu,
+    osd::{OsdKind, OsdLevel},
     password_dialog,
     style::{
         HeaderButtonStyle, QuickSettingsButtonStyle, QuickSettingsSubMenuButtonStyle,
@@ -50,6 +55,7 @@ pub struct Settings {
     sub_menu: Option<SubMenu>,
     battery_data: Option<BatteryData>,
     pub password_dialog: Option<(String, String)>,
+    pub bluetooth_dialog: Option<(AgentRequest, String)>,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -72,6 +78,7 @@ pub enum Message {
     Power(PowerMessage),
     ToggleSubMenu(SubMenu),
     PasswordDialog(password_dialog::Message),
+    BluetoothDialog(bluetooth_dialog::Message),
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
@@ -79,6 +86,7 @@ pub enum SubMenu {
     Power,
     Sinks,
     Sources,
+    Mixer,
     Wifi,
     Vpn,
     Bluetooth,
@@ -96,6 +104,7 @@ impl Settings {
             sub_menu: None,
             battery_data: None,
             password_dialog: None,
+            bluetooth_dialog: None,
         }
     }
 
@@ -108,7 +117,7 @@ impl Settings {
         match message {
             Message::ToggleMenu => {
                 self.sub_menu = None;
-                self.password_dialog = None;
+                self.close_dialogs();
                 iced::Command::batch(vec![
                     menu.unset_keyboard_interactivity(),
                     menu.toggle(MenuType::Settings),
@@ -142,7 +151,13 @@ impl Settings {
             Message::Net(msg) => self
                 .net
                 .update(msg, menu, &mut self.password_dialog, config),
-            Message::Bluetooth(msg) => self.bluetooth.update(msg, menu, &mut self.sub_menu, config),
+            Message::Bluetooth(msg) => self.bluetooth.update(
+                msg,
+                menu,
+                &mut self.sub_menu,
+                &mut self.bluetooth_dialog,
+                config,
+            ),
             Message::PowerProfiles(msg) => self.powerprofiles.update(msg),
             Message::Audio(msg) => self.audio.update(msg, menu, config),
             Message::Brightness(msg) => self.brightness.update(msg),
@@ -157,6 +172,9 @@ impl Settings {
                         SubMenu::Wifi => {
                             self.net.get_nearby_wifi();
                         }
+                        SubMenu::Bluetooth => {
+                            self.bluetooth.start_discovery();
+                        }
                         _ => {}
                     };
                     self.sub_menu.replace(menu_type);
@@ -203,6 +221,47 @@ impl Settings {
                     }
                 }
             },
+            Message::BluetoothDialog(msg) => match msg {
+                bluetooth_dialog::Message::InputChanged(input) => {
+                    if let Some((_, current_input)) = &mut self.bluetooth_dialog {
+                        *current_input = input;
+                    }
+
+                    iced::Command::none()
+                }
+                bluetooth_dialog::Message::DialogConfirmed => {
+                    if let Some((_, input)) = self.bluetooth_dialog.take() {
+                        self.bluetooth.agent_reply(Some(input));
+                        menu.unset_keyboard_interactivity()
+                    } else {
+                        iced::Command::none()
+                    }
+                }
+                bluetooth_dialog::Message::DialogCancelled => {
+                    if let Some((_, _)) = self.bluetooth_dialog.take() {
+                        self.bluetooth.agent_reply(None);
+                        menu.unset_keyboard_interactivity()
+                    } else {
+                        iced::Command::none()
+                    }
+                }
+                bluetooth_dialog::Message::DialogDismissed => {
+                    self.bluetooth_dialog = None;
+
+                    iced::Command::none()
+                }
+            },
+        }
+    }
+
+    /// Drops the open dialogs, a pending bluetooth request is rejected.
+    /// The dialogs are shown in the settings menu, this is called whenever the menu closes
+    pub fn close_dialogs(&mut self) {
+        self.password_dialog = None;
+        if let Some((request, _)) = self.bluetooth_dialog.take() {
+            if request.kind.expects_reply() {
+                self.bluetooth.agent_reply(None);
+            }
         }
     }
 
@@ -228,7 +287,9 @@ impl Settings {
         }
 
         if let Some(sink_indicator) = self.audio.sink_indicator() {
-            elements = elements.push(sink_indicator);
+            elements = elements.push(scroll_area(sink_indicator, |direction| {
+                Message::Audio(AudioMessage::SinkVolumeStep(direction))
+            }));
         }
 
         let mut net_elements = column!().spacing(4);
@@ -255,6 +316,8 @@ impl Settings {
     pub fn menu_view(&self, config: &SettingsModuleConfig) -> Element<Message> {
         Column::with_children(if let Some((_, current_password)) = &self.password_dialog {
             vec![password_dialog::view("ssid", current_password).map(Message::PasswordDialog)]
+        } else if let Some((request, current_input)) = &self.bluetooth_dialog {
+            vec![bluetooth_dialog::view(request, current_input).map(Message::BluetoothDialog)]
         } else {
             let battery_data = self.battery_data.map(settings_battery_indicator);
             let right_buttons = Row::with_children(
@@ -292,7 +355,9 @@ impl Settings {
                 row!(Space::with_width(Length::Fill), right_buttons).width(Length::Fill)
             };
 
-            let (sink_slider, source_slider) = self.audio.audio_sliders(self.sub_menu);
+            let (sink_slider, source_slider) = self
+                .audio
+                .audio_sliders(self.sub_menu, audio::max_volume(config));
 
             let wifi_setting_button = self
                 .net
@@ -307,6 +372,7 @@ impl Settings {
                         config.bluetooth_more_cmd.is_some(),
                     ),
                     self.powerprofiles.get_quick_setting_button(),
+                    self.audio.get_noise_suppression_quick_setting_button(),
                     self.idle_inhibitor.as_ref().map(|idle_inhibitor| {
                         (
                             quick_setting_button(
@@ -353,6 +419,10 @@ impl Settings {
                                 .sources_submenu(config.audio_sources_more_cmd.is_some()),
                         )
                     }),
+                self.audio.mixer_button(self.sub_menu),
+                self.sub_menu
+                    .filter(|menu_type| *menu_type == SubMenu::Mixer)
+                    .map(|_| sub_menu_wrapper(self.audio.mixer_submenu())),
                 Some(self.brightness.brightness_slider()),
                 Some(quick_settings),
             ]
@@ -366,10 +436,53 @@ impl Settings {
         .into()
     }
 
-    pub fn subscription(&self) -> Subscription<Message> {
+    /// Current levels, the OSD is shown when one of them changes
+    pub fn osd_levels(&self) -> Vec<OsdLevel> {
+        [
+            self.audio
+                .sink_level()
+                .map(|(device, value, muted)| OsdLevel {
+                    kind: OsdKind::Volume,
+                    device,
+                    value,
+                    muted,
+                }),
+            self.audio
+                .source_level()
+                .map(|(device, value, muted)| OsdLevel {
+                    kind: OsdKind::Microphone,
+                    device,
+                    value,
+                    muted,
+                }),
+            self.brightness.level().map(|value| OsdLevel {
+                kind: OsdKind::Brightness,
+                device: String::new(),
+                value,
+                muted: false,
+            }),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+
+    /// The source meter only runs while the settings menu is open
+    pub fn subscription(
+        &self,
+        config: &SettingsModuleConfig,
+        menu_open: bool,
+    ) -> Subscription<Message> {
         iced::Subscription::batch(vec![
             crate::utils::battery::subscription().map(Message::Battery),
-            self.audio.subscription().map(Message::Audio),
+            self.audio
+                .subscription(config.audio_backend)
+                .map(Message::Audio),
+            if menu_open {
+                self.audio.source_meter_subscription().map(Message::Audio)
+            } else {
+                Subscription::none()
+            },
             self.brightness.subscription().map(Message::Brightness),
             self.net.subscription().map(Message::Net),
             self.bluetooth.subscription().map(Message::Bluetooth),
@@ -0,0 +1,183 @@
+use crate::{
+    components::icons::{icon, Icons},
+    config::SubmapModuleConfig,
+};
+use hyprland::{
+    data::{Client, Workspace},
+    event_listener::EventListener,
+    shared::{HyprDataActive, HyprDataActiveOptional},
+};
+use iced::{
+    widget::{container, text, Column},
+    Alignment, Border, Color, Element, Theme,
+};
+use std::cell::RefCell;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FullscreenState {
+    None,
+    Fullscreen,
+    Maximized,
+}
+
+fn get_fullscreen_state() -> FullscreenState {
+    let fullscreen = Workspace::get_active()
+        .map(|w| w.fullscreen)
+        .unwrap_or_default();
+
+    if fullscreen {
+        // hyprland fullscreen mode 1 means maximized
+        match Client::get_active().ok().flatten() {
+            Some(client) if client.fullscreen && client.fullscreen_mode == 1 => {
+                FullscreenState::Maximized
+            }
+            _ => FullscreenState::Fullscreen,
+        }
+    } else {
+        FullscreenState::None
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    SubmapChanged(String),
+    FullscreenChanged(FullscreenState),
+}
+
+pub struct Submap {
+    submap: Option<String>,
+    fullscreen: FullscreenState,
+}
+
+impl Submap {
+    pub fn new() -> Self {
+        Self {
+            submap: None,
+            fullscreen: get_fullscreen_state(),
+        }
+    }
+
+    pub fn update(&mut self, message: Message) {
+        match message {
+            Message::SubmapChanged(submap) => {
+                self.submap = Some(submap).filter(|s| !s.is_empty());
+            }
+            Message::FullscreenChanged(state) => {
+                self.fullscreen = state;
+            }
+        }
+    }
+
+    pub fn view(&self, config: &SubmapModuleConfig) -> Option<Element<Message>> {
+        if config.disabled {
+            return None;
+        }
+
+        let submap = self.submap.as_ref().map(|submap| {
+            let submap_config = config.submaps.get(submap);
+            let label = submap_config
+                .and_then(|c| c.label.clone())
+                .unwrap_or_else(|| submap.clone());
+            let color = submap_config
+                .and_then(|c| c.color)
+                .map(|c| Color::from_rgb8(c.r, c.g, c.b));
+
+            container(text(label).size(10))
+                .padding([4, 6])
+                .style(move |theme: &Theme| {
+                    let background = color.unwrap_or(theme.palette().primary);
+                    container::Appearance {
+                        background: Some(background.into()),
+                        border: Border::with_radius(8),
+                        text_color: Some(theme.palette().background),
+                        ..Default::default()
+                    }
+                })
+                .into()
+        });
+
+        let fullscreen = match self.fullscreen {
+            FullscreenState::None => None,
+            FullscreenState::Fullscreen => Some(Icons::WindowFullscreen),
+            FullscreenState::Maximized => Some(Icons::WindowMaximized),
+        }
+        .map(|icon_type| {
+            container(icon(icon_type))
+                .style(|theme: &Theme| container::Appearance {
+                    text_color: Some(theme.palette().primary),
+                    ..Default::default()
+                })
+                .into()
+        });
+
+        if submap.is_none() && fullscreen.is_none() {
+            None
+        } else {
+            Some(
+                Column::with_children(
+                    vec![submap, fullscreen]
+                        .into_iter()
+                        .flatten()
+                        .collect::<Vec<_>>(),
+                )
+                .align_items(Alignment::Center)
+                .spacing(4)
+                .into(),
+            )
+        }
+    }
+
+    pub fn subscription(&self) -> iced::Subscription<Message> {
+        iced::subscription::channel("submap-listener", 10, |output| async move {
+            let output = RefCell::new(output);
+            let mut event_listener = EventListener::new();
+
+            event_listener.add_sub_map_change_handler({
+                let output = output.clone();
+                move |submap| {
+                    let mut output = output.borrow_mut();
+                    output
+                        .try_send(Message::SubmapChanged(submap))
+                        .expect("error sending submap: submap change event");
+                }
+            });
+
+            event_listener.add_fullscreen_state_change_handler({
+                let output = output.clone();
+                move |_| {
+                    let mut output = output.borrow_mut();
+                    output
+                        .try_send(Message::FullscreenChanged(get_fullscreen_state()))
+                        .expect("error getting fullscreen state: fullscreen change event");
+                }
+            });
+
+            event_listener.add_workspace_change_handler({
+                let output = output.clone();
+                move |_| {
+                    let mut output = output.borrow_mut();
+                    output
+                        .try_send(Message::FullscreenChanged(get_fullscreen_state()))
+                        .expect("error getting fullscreen state: workspace change event");
+                }
+            });
+
+            event_listener.add_active_monitor_change_handler({
+                let output = output.clone();
+                move |_| {
+                    let mut output = output.borrow_mut();
+                    output
+                        .try_send(Message::FullscreenChanged(get_fullscreen_state()))
+                        .expect("error getting fullscreen state: active monitor change event");
+                }
+            });
+
+            event_listener
+                .start_listener_async()
+                .await
+                .expect("failed to start submap listener");
+
+            panic!("Exiting hyprland event listener");
+        })
+    }
+}
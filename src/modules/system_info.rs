@@ -1,41 +1,266 @@
 use crate::{
-    components::icons::{icon, Icons},
-    config::SystemModuleConfig,
+    components::{
+        icons::{icon, Icons},
+        sparkline::{push_sample, sparkline},
+    },
+    config::{AlertRule, SystemMetric, SystemModuleConfig, TemperatureSensor, TemperatureUnit},
+    menu::{Menu, MenuType},
+    style::{GhostButtonStyle, HeaderButtonStyle, QuickSettingsButtonStyle, SettingsButtonStyle},
+    utils::{launcher::execute_command, notifications::notify},
 };
 use iced::{
-    widget::{column, container, row, text},
-    Element, Length, Theme,
+    theme::Button,
+    widget::{button, column, container, horizontal_rule, progress_bar, row, text, Column},
+    Alignment, Element, Length, Theme,
 };
-use std::time::Duration;
-use sysinfo::{Components, System};
+use log::warn;
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    time::{Duration, Instant},
+};
+use sysinfo::{
+    Component, Components, CpuRefreshKind, Pid, Process, ProcessRefreshKind, Signal, System,
+};
+
+/// Samples kept in the history graphs, one sample every `HISTORY_INTERVAL`
+const HISTORY_SIZE: usize = 60;
+const HISTORY_INTERVAL: Duration = Duration::from_secs(5);
+const TOP_PROCESSES: usize = 8;
 
+#[derive(Default)]
 struct SystemInfoData {
     pub cpu_usage: u32,
+    pub cpu_cores: Vec<u32>,
+    pub cpu_frequency: u64,
     pub memory_usage: u32,
-    pub temperature: Option<f32>,
+    pub swap_usage: Option<u32>,
+    pub load_average: f64,
+    pub uptime: u64,
+}
+
+/// Data source refreshed to read a metric, metrics sharing a source are refreshed once
+#[derive(PartialEq, Eq, Hash)]
+enum Source {
+    CpuUsage,
+    CpuFrequency,
+    Memory,
+    Components,
+    System,
 }
 
-fn get_system_info(system: &mut System, components: &mut Components) -> SystemInfoData {
-    system.refresh_memory();
-    system.refresh_cpu_specifics(sysinfo::CpuRefreshKind::everything());
+impl From<SystemMetric> for Source {
+    fn from(metric: SystemMetric) -> Self {
+        match metric {
+            SystemMetric::Cpu | SystemMetric::CpuCores => Source::CpuUsage,
+            SystemMetric::CpuFrequency => Source::CpuFrequency,
+            SystemMetric::Memory | SystemMetric::Swap => Source::Memory,
+            SystemMetric::Temperature => Source::Components,
+            SystemMetric::LoadAverage | SystemMetric::Uptime => Source::System,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum Level {
+    Normal,
+    Warn,
+    Alert,
+}
+
+pub fn level<T: PartialOrd>(value: T, warn_threshold: T, alert_threshold: T) -> Level {
+    if value >= alert_threshold {
+        Level::Alert
+    } else if value > warn_threshold {
+        Level::Warn
+    } else {
+        Level::Normal
+    }
+}
+
+fn format_uptime(uptime: u64) -> String {
+    let minutes = uptime / 60;
+    let hours = minutes / 60;
+    let days = hours / 24;
+
+    if days > 0 {
+        format!("{}d", days)
+    } else if hours > 0 {
+        format!("{}h", hours)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+
+#[derive(Default)]
+struct History {
+    cpu: VecDeque<f32>,
+    memory: VecDeque<f32>,
+    temperature: VecDeque<f32>,
+    last_sample: Option<Instant>,
+}
+
+/// Tracks how long a metric stayed over the alert threshold
+#[derive(Debug, Clone, Default)]
+struct AlertState {
+    above_since: Option<Instant>,
+    active: bool,
+}
+
+impl AlertState {
+    /// Returns true when the alert fires, a fired alert is re-armed only when the value
+    /// goes back under the threshold minus the hysteresis
+    fn check(&mut self, rule: &AlertRule, value: f32, now: Instant) -> bool {
+        if value >= rule.threshold {
+            let above_since = *self.above_since.get_or_insert(now);
+
+            if !self.active && now.duration_since(above_since).as_secs() >= rule.duration {
+                self.active = true;
+                return true;
+            }
+        } else if !self.active || value < rule.threshold - rule.hysteresis {
+            self.above_since = None;
+            self.active = false;
+        }
+
+        false
+    }
+}
+
+fn metric_label(metric: SystemMetric) -> &'static str {
+    match metric {
+        SystemMetric::Cpu => "CPU usage",
+        SystemMetric::CpuCores => "CPU cores usage",
+        SystemMetric::CpuFrequency => "CPU frequency",
+        SystemMetric::Memory => "Memory usage",
+        SystemMetric::Swap => "Swap usage",
+        SystemMetric::Temperature => "Temperature",
+        SystemMetric::LoadAverage => "Load average",
+        SystemMetric::Uptime => "Uptime",
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ProcessSort {
+    #[default]
+    Cpu,
+    Memory,
+}
+
+fn format_memory(bytes: u64) -> String {
+    let mb = bytes as f64 / 1024. / 1024.;
+
+    if mb >= 1024. {
+        format!("{:.1} GB", mb / 1024.)
+    } else {
+        format!("{:.0} MB", mb)
+    }
+}
 
-    components.refresh_list();
-    components.refresh();
+pub fn metric_view<'a, Msg: 'a>(
+    content: impl Into<Element<'a, Msg>>,
+    level: Level,
+) -> Element<'a, Msg> {
+    container(content)
+        .style(move |theme: &Theme| container::Appearance {
+            text_color: match level {
+                Level::Normal => None,
+                Level::Warn => Some(theme.extended_palette().danger.weak.color),
+                Level::Alert => Some(theme.palette().danger),
+            },
+            ..Default::default()
+        })
+        .into()
+}
 
-    let cpu_usage = system.global_cpu_info().cpu_usage().floor() as u32;
-    let memory_usage = ((system.total_memory() - system.available_memory()) as f32
-        / system.total_memory() as f32
-        * 100.) as u32;
+fn is_selected_sensor(component: &Component, sensor: &TemperatureSensor) -> bool {
+    match sensor {
+        TemperatureSensor::Max => true,
+        TemperatureSensor::Label(label) => component.label() == label,
+        TemperatureSensor::Regex(regex) => regex.is_match(component.label()),
+    }
+}
 
-    let temperature = components
+fn get_temperature(components: &Components, sensor: &TemperatureSensor) -> Option<f32> {
+    components
         .iter()
-        .find(|c| c.label() == "acpitz temp1")
-        .map(|c| c.temperature());
+        .filter(|c| is_selected_sensor(c, sensor))
+        .map(|c| c.temperature())
+        .filter(|t| t.is_finite())
+        .reduce(f32::max)
+}
 
-    SystemInfoData {
-        cpu_usage,
-        memory_usage,
-        temperature,
+fn convert_temperature(celsius: f32, unit: TemperatureUnit) -> f32 {
+    match unit {
+        TemperatureUnit::Celsius => celsius,
+        TemperatureUnit::Fahrenheit => celsius * 9. / 5. + 32.,
+    }
+}
+
+fn format_temperature(celsius: f32, unit: TemperatureUnit) -> String {
+    format!(
+        "{:.0}{}",
+        convert_temperature(celsius, unit),
+        match unit {
+            TemperatureUnit::Celsius => "°C",
+            TemperatureUnit::Fahrenheit => "°F",
+        }
+    )
+}
+
+fn refresh_source(system: &mut System, components: &mut Components, source: &Source) {
+    match source {
+        Source::CpuUsage => system.refresh_cpu_specifics(CpuRefreshKind::new().with_cpu_usage()),
+        Source::CpuFrequency => {
+            system.refresh_cpu_specifics(CpuRefreshKind::new().with_frequency())
+        }
+        Source::Memory => system.refresh_memory(),
+        Source::Components => {
+            components.refresh_list();
+            components.refresh();
+        }
+        Source::System => {}
+    }
+}
+
+fn read_metric(system: &System, data: &mut SystemInfoData, metric: SystemMetric) {
+    match metric {
+        SystemMetric::Cpu => {
+            data.cpu_usage = system.global_cpu_info().cpu_usage().floor() as u32;
+        }
+        SystemMetric::CpuCores => {
+            data.cpu_cores = system
+                .cpus()
+                .iter()
+                .map(|cpu| cpu.cpu_usage().floor() as u32)
+                .collect();
+        }
+        SystemMetric::CpuFrequency => {
+            let cpus = system.cpus();
+            data.cpu_frequency = if cpus.is_empty() {
+                0
+            } else {
+                cpus.iter().map(|cpu| cpu.frequency()).sum::<u64>() / cpus.len() as u64
+            };
+        }
+        SystemMetric::Memory => {
+            data.memory_usage = ((system.total_memory() - system.available_memory()) as f32
+                / system.total_memory() as f32
+                * 100.) as u32;
+        }
+        SystemMetric::Swap => {
+            data.swap_usage = if system.total_swap() > 0 {
+                Some((system.used_swap() as f32 / system.total_swap() as f32 * 100.) as u32)
+            } else {
+                None
+            };
+        }
+        SystemMetric::LoadAverage => {
+            data.load_average = System::load_average().one;
+        }
+        SystemMetric::Uptime => {
+            data.uptime = System::uptime();
+        }
+        SystemMetric::Temperature => {}
     }
 }
 
@@ -43,124 +268,563 @@ pub struct SystemInfo {
     system: System,
     components: Components,
     data: SystemInfoData,
+    last_update: HashMap<SystemMetric, Instant>,
+    history: History,
+    process_sort: ProcessSort,
+    selected_process: Option<Pid>,
+    alerts: Vec<AlertState>,
 }
 
 #[derive(Debug, Clone)]
 pub enum Message {
-    Update,
+    Update(Instant),
+    ToggleMenu,
+    SortProcesses(ProcessSort),
+    SelectProcess(Pid),
+    TerminateProcess,
+    KillProcess,
 }
 
 impl SystemInfo {
     pub fn new() -> Self {
         let mut system = System::new();
-        let mut components = Components::new_with_refreshed_list();
-        let data = get_system_info(&mut system, &mut components);
+        system.refresh_cpu_specifics(CpuRefreshKind::everything());
+        system.refresh_memory();
 
         Self {
             system,
-            components,
-            data,
+            components: Components::new_with_refreshed_list(),
+            data: SystemInfoData::default(),
+            last_update: HashMap::new(),
+            history: History::default(),
+            process_sort: ProcessSort::default(),
+            selected_process: None,
+            alerts: Vec::new(),
+        }
+    }
+
+    fn refresh_processes(&mut self) {
+        self.system
+            .refresh_processes_specifics(ProcessRefreshKind::new().with_cpu().with_memory());
+    }
+
+    fn refresh(&mut self, config: &SystemModuleConfig, now: Instant) {
+        let sample_history = self
+            .history
+            .last_sample
+            .map_or(true, |last| now.duration_since(last) >= HISTORY_INTERVAL);
+
+        // alert metrics are refreshed with their own interval even when they are not shown
+        let metrics = config
+            .metrics
+            .iter()
+            .copied()
+            .chain(config.alerts.iter().map(|rule| rule.metric))
+            .filter(|metric| {
+                // half a tick of tolerance, the update message is fired every second
+                self.last_update.get(metric).map_or(true, |last| {
+                    now.duration_since(*last) + Duration::from_millis(500)
+                        >= config.interval(*metric)
+                })
+            })
+            .collect::<HashSet<_>>();
+
+        let mut sources = metrics
+            .iter()
+            .map(|metric| Source::from(*metric))
+            .collect::<HashSet<_>>();
+        if sample_history {
+            sources.extend([Source::CpuUsage, Source::Memory, Source::Components]);
+        }
+
+        for source in sources {
+            refresh_source(&mut self.system, &mut self.components, &source);
+        }
+
+        for metric in &metrics {
+            read_metric(&self.system, &mut self.data, *metric);
+            self.last_update.insert(*metric, now);
         }
+
+        if sample_history {
+            let memory = (self.system.total_memory() - self.system.available_memory()) as f32
+                / self.system.total_memory() as f32
+                * 100.;
+
+            push_sample(
+                &mut self.history.cpu,
+                self.system.global_cpu_info().cpu_usage(),
+                HISTORY_SIZE,
+            );
+            push_sample(&mut self.history.memory, memory, HISTORY_SIZE);
+            push_sample(
+                &mut self.history.temperature,
+                get_temperature(&self.components, &config.temperature_sensor).unwrap_or_default(),
+                HISTORY_SIZE,
+            );
+            self.history.last_sample = Some(now);
+        }
+
+        self.check_alerts(config, &metrics, now);
     }
 
-    pub fn update(&mut self, message: Message) {
+    /// Current value of the metric as compared with the alert thresholds
+    fn alert_value(&self, config: &SystemModuleConfig, metric: SystemMetric) -> Option<f32> {
+        match metric {
+            SystemMetric::Cpu => Some(self.system.global_cpu_info().cpu_usage()),
+            SystemMetric::Memory => Some(
+                (self.system.total_memory() - self.system.available_memory()) as f32
+                    / self.system.total_memory() as f32
+                    * 100.,
+            ),
+            SystemMetric::Swap => (self.system.total_swap() > 0)
+                .then(|| self.system.used_swap() as f32 / self.system.total_swap() as f32 * 100.),
+            SystemMetric::Temperature => {
+                get_temperature(&self.components, &config.temperature_sensor)
+            }
+            SystemMetric::LoadAverage => Some(
+                (System::load_average().one / self.system.cpus().len().max(1) as f64 * 100.) as f32,
+            ),
+            SystemMetric::CpuCores | SystemMetric::CpuFrequency | SystemMetric::Uptime => None,
+        }
+    }
+
+    /// Checks the alerts of the metrics refreshed in this update
+    fn check_alerts(
+        &mut self,
+        config: &SystemModuleConfig,
+        refreshed: &HashSet<SystemMetric>,
+        now: Instant,
+    ) {
+        self.alerts
+            .resize(config.alerts.len(), AlertState::default());
+
+        for (index, rule) in config.alerts.iter().enumerate() {
+            if !refreshed.contains(&rule.metric) {
+                continue;
+            }
+            let Some(value) = self.alert_value(config, rule.metric) else {
+                continue;
+            };
+
+            if self.alerts[index].check(rule, value, now) {
+                let value = if rule.metric == SystemMetric::Temperature {
+                    format_temperature(value, config.temperature_unit)
+                } else {
+                    format!("{:.0}%", value)
+                };
+                let summary = format!("{} is high", metric_label(rule.metric));
+                let body = format!(
+                    "{} has been at {} for more than {} seconds",
+                    metric_label(rule.metric),
+                    value,
+                    rule.duration
+                );
+                warn!("{}", body);
+
+                if rule.notify {
+                    notify(summary, body, rule.urgency);
+                }
+                if let Some(command) = &rule.command {
+                    execute_command(command.clone());
+                }
+            }
+        }
+    }
+
+    fn signal_selected_process(&mut self, signal: Signal) {
+        if let Some(process) = self
+            .selected_process
+            .take()
+            .and_then(|pid| self.system.process(pid))
+        {
+            if !process.kill_with(signal).unwrap_or(false) {
+                warn!("failed to send {:?} to process {}", signal, process.pid());
+            }
+        }
+
+        self.refresh_processes();
+    }
+
+    pub fn update(
+        &mut self,
+        message: Message,
+        config: &SystemModuleConfig,
+        menu: &mut Menu,
+    ) -> iced::Command<Message> {
         match message {
-            Message::Update => {
-                let data = get_system_info(&mut self.system, &mut self.components);
-                self.data = data;
+            Message::Update(now) => {
+                self.refresh(config, now);
+
+                // processes are expensive to read, do it only while the menu is open
+                if menu.get_menu_type() == Some(MenuType::SystemInfo) {
+                    self.refresh_processes();
+                }
+
+                iced::Command::none()
+            }
+            Message::ToggleMenu => {
+                self.selected_process = None;
+                self.refresh_processes();
+
+                menu.toggle(MenuType::SystemInfo)
+            }
+            Message::SortProcesses(sort) => {
+                self.process_sort = sort;
+
+                iced::Command::none()
+            }
+            Message::SelectProcess(pid) => {
+                if self.selected_process == Some(pid) {
+                    self.selected_process = None;
+                } else {
+                    self.selected_process = Some(pid);
+                }
+
+                iced::Command::none()
+            }
+            Message::TerminateProcess => {
+                self.signal_selected_process(Signal::Term);
+
+                iced::Command::none()
+            }
+            Message::KillProcess => {
+                self.signal_selected_process(Signal::Kill);
+
+                iced::Command::none()
             }
         }
     }
 
+    fn metric_indicator(
+        &self,
+        config: &SystemModuleConfig,
+        metric: SystemMetric,
+    ) -> Option<Element<Message>> {
+        let with_icon = |icon_type: Icons, value: String, level: Level| {
+            metric_view(
+                column!(icon(icon_type), text(value))
+                    .spacing(4)
+                    .align_items(Alignment::Center),
+                level,
+            )
+        };
+
+        match metric {
+            SystemMetric::Cpu => Some(with_icon(
+                Icons::Cpu,
+                self.data.cpu_usage.to_string(),
+                level(
+                    self.data.cpu_usage,
+                    config.cpu_warn_threshold,
+                    config.cpu_alert_threshold,
+                ),
+            )),
+            SystemMetric::CpuCores => Some(
+                column!(
+                    icon(Icons::Cpu),
+                    Column::with_children(
+                        self.data
+                            .cpu_cores
+                            .iter()
+                            .map(|usage| {
+                                metric_view(
+                                    progress_bar(0.0..=100.0, *usage as f32)
+                                        .width(Length::Fixed(20.))
+                                        .height(Length::Fixed(3.)),
+                                    level(
+                                        *usage,
+                                        config.cpu_warn_threshold,
+                                        config.cpu_alert_threshold,
+                                    ),
+                                )
+                            })
+                            .collect::<Vec<_>>(),
+                    )
+                    .spacing(1)
+                )
+                .spacing(4)
+                .align_items(Alignment::Center)
+                .into(),
+            ),
+            SystemMetric::CpuFrequency => Some(with_icon(
+                Icons::CpuFrequency,
+                format!("{:.1}", self.data.cpu_frequency as f32 / 1000.),
+                Level::Normal,
+            )),
+            SystemMetric::Memory => Some(with_icon(
+                Icons::Mem,
+                self.data.memory_usage.to_string(),
+                level(
+                    self.data.memory_usage,
+                    config.mem_warn_threshold,
+                    config.mem_alert_threshold,
+                ),
+            )),
+            SystemMetric::Swap => self.data.swap_usage.map(|swap_usage| {
+                with_icon(
+                    Icons::Swap,
+                    swap_usage.to_string(),
+                    level(
+                        swap_usage,
+                        config.swap_warn_threshold,
+                        config.swap_alert_threshold,
+                    ),
+                )
+            }),
+            SystemMetric::Temperature => {
+                let temperature = get_temperature(&self.components, &config.temperature_sensor)
+                    .unwrap_or_default();
+
+                Some(with_icon(
+                    Icons::Temp,
+                    (convert_temperature(temperature, config.temperature_unit) as i32).to_string(),
+                    level(
+                        temperature as i32,
+                        config.temp_warn_threshold,
+                        config.temp_alert_threshold,
+                    ),
+                ))
+            }
+            SystemMetric::LoadAverage => {
+                // thresholds are a percentage of the available cpu cores
+                let cores = self.system.cpus().len().max(1) as f64;
+
+                Some(with_icon(
+                    Icons::Load,
+                    format!("{:.1}", self.data.load_average),
+                    level(
+                        (self.data.load_average / cores * 100.) as u32,
+                        config.load_warn_threshold,
+                        config.load_alert_threshold,
+                    ),
+                ))
+            }
+            SystemMetric::Uptime => Some(with_icon(
+                Icons::Uptime,
+                format_uptime(self.data.uptime),
+                Level::Normal,
+            )),
+        }
+    }
+
     pub fn view(&self, config: &SystemModuleConfig) -> Option<Element<Message>> {
         if config.disabled {
             None
         } else {
-            let cpu_usage = self.data.cpu_usage;
-            let memory_usage = self.data.memory_usage;
-            let temperature = self.data.temperature.unwrap_or_default() as i32;
-
-            let cpu_warn_threshold = config.cpu_warn_threshold;
-            let cpu_alert_threshold = config.cpu_alert_threshold;
-
-            let mem_warn_threshold = config.mem_warn_threshold;
-            let mem_alert_threshold = config.mem_alert_threshold;
-
-            let temp_warn_threshold = config.temp_warn_threshold;
-            let temp_alert_threshold = config.temp_alert_threshold;
             Some(
-                container(
-                    column!(
-                        container(
-                            column!(icon(Icons::Cpu), text(format!("{}", cpu_usage)))
-                                .spacing(4)
-                                .align_items(iced::Alignment::Center),
-                        )
-                        .style(move |theme: &Theme| {
-                            container::Appearance {
-                                text_color: if cpu_usage > cpu_warn_threshold
-                                    && cpu_usage < cpu_alert_threshold
-                                {
-                                    Some(theme.extended_palette().danger.weak.color)
-                                } else if cpu_usage >= cpu_alert_threshold {
-                                    Some(theme.palette().danger)
-                                } else {
-                                    None
-                                },
-                                ..Default::default()
-                            }
-                        }),
-                        container(
-                            column!(icon(Icons::Mem), text(format!("{}", memory_usage)))
-                                .spacing(4)
-                                .align_items(iced::Alignment::Center),
-                        )
-                        .style(move |theme: &Theme| {
-                            container::Appearance {
-                                text_color: if memory_usage > mem_warn_threshold
-                                    && memory_usage < mem_alert_threshold
-                                {
-                                    Some(theme.extended_palette().danger.weak.color)
-                                } else if memory_usage >= mem_alert_threshold {
-                                    Some(theme.palette().danger)
-                                } else {
-                                    None
-                                },
-                                ..Default::default()
-                            }
-                        }),
-                        container(
-                            column!(icon(Icons::Temp), text(format!("{}", temperature)))
-                                .spacing(4)
-                                .align_items(iced::Alignment::Center),
-                        )
-                        .style(move |theme: &Theme| {
-                            container::Appearance {
-                                text_color: if temperature > temp_warn_threshold
-                                    && temperature < temp_alert_threshold
-                                {
-                                    Some(theme.extended_palette().danger.weak.color)
-                                } else if temperature >= temp_alert_threshold {
-                                    Some(theme.palette().danger)
-                                } else {
-                                    None
-                                },
-                                ..Default::default()
-                            }
-                        })
+                button(
+                    Column::with_children(
+                        config
+                            .metrics
+                            .iter()
+                            .filter_map(|metric| self.metric_indicator(config, *metric))
+                            .collect::<Vec<_>>(),
                     )
-                    .align_items(iced::Alignment::Center)
+                    .align_items(Alignment::Center)
                     .spacing(4),
                 )
-                .align_y(iced::alignment::Vertical::Center)
                 .width(Length::Fill)
-                //.padding([2, 7])
+                .padding([4, 0])
+                .style(Button::custom(HeaderButtonStyle::Full))
+                .on_press(Message::ToggleMenu)
                 .into(),
             )
         }
     }
 
+    fn history_view(&self, config: &SystemModuleConfig) -> Element<Message> {
+        let unit = config.temperature_unit;
+        let graph = |label: &str, value: String, samples: &VecDeque<f32>, max: f32| {
+            column!(
+                row!(
+                    text(label.to_string()).size(12).width(Length::Fill),
+                    text(value).size(12)
+                ),
+                sparkline(samples, max)
+            )
+            .spacing(4)
+        };
+
+        column!(
+            graph(
+                "CPU",
+                format!("{:.0}%", self.history.cpu.back().unwrap_or(&0.)),
+                &self.history.cpu,
+                100.
+            ),
+            graph(
+                "Memory",
+                format!("{:.0}%", self.history.memory.back().unwrap_or(&0.)),
+                &self.history.memory,
+                100.
+            ),
+            graph(
+                "Temperature",
+                format_temperature(*self.history.temperature.back().unwrap_or(&0.), unit),
+                &self.history.temperature,
+                self.history
+                    .temperature
+                    .iter()
+                    .copied()
+                    .fold(100., f32::max)
+            ),
+        )
+        .spacing(8)
+        .into()
+    }
+
+    fn processes_view(&self) -> Element<Message> {
+        let mut processes = self.system.processes().values().collect::<Vec<&Process>>();
+        match self.process_sort {
+            ProcessSort::Cpu => processes.sort_by(|a, b| b.cpu_usage().total_cmp(&a.cpu_usage())),
+            ProcessSort::Memory => processes.sort_by_key(|p| std::cmp::Reverse(p.memory())),
+        }
+
+        let header = row!(
+            text("Processes").size(12).width(Length::Fill),
+            button(text("CPU").size(10))
+                .padding([2, 8])
+                .on_press(Message::SortProcesses(ProcessSort::Cpu))
+                .style(Button::custom(QuickSettingsButtonStyle(
+                    self.process_sort == ProcessSort::Cpu
+                ))),
+            button(text("Memory").size(10))
+                .padding([2, 8])
+                .on_press(Message::SortProcesses(ProcessSort::Memory))
+                .style(Button::custom(QuickSettingsButtonStyle(
+                    self.process_sort == ProcessSort::Memory
+                ))),
+        )
+        .spacing(4)
+        .align_items(Alignment::Center);
+
+        let list = Column::with_children(
+            processes
+                .into_iter()
+                .take(TOP_PROCESSES)
+                .map(|process| {
+                    let selected = self.selected_process == Some(process.pid());
+
+                    button(
+                        row!(
+                            text(process.name()).size(12).width(Length::Fill),
+                            text(process.pid().to_string())
+                                .size(10)
+                                .width(Length::Fixed(50.)),
+                            text(format!("{:.1}%", process.cpu_usage()))
+                                .size(12)
+                                .width(Length::Fixed(50.)),
+                            text(format_memory(process.memory()))
+                                .size(12)
+                                .width(Length::Fixed(60.)),
+                        )
+                        .spacing(8)
+                        .align_items(Alignment::Center),
+                    )
+                    .padding([2, 8])
+                    .width(Length::Fill)
+                    .on_press(Message::SelectProcess(process.pid()))
+                    .style(if selected {
+                        Button::custom(QuickSettingsButtonStyle(true))
+                    } else {
+                        Button::custom(GhostButtonStyle)
+                    })
+                    .into()
+                })
+                .collect::<Vec<Element<'_, _, _>>>(),
+        )
+        .spacing(2);
+
+        let actions = self
+            .selected_process
+            .and_then(|pid| self.system.process(pid))
+            .map(|process| {
+                row!(
+                    text(format!("{} ({})", process.name(), process.pid()))
+                        .size(12)
+                        .width(Length::Fill),
+                    button(text("Terminate").size(12))
+                        .padding([4, 8])
+                        .on_press(Message::TerminateProcess)
+                        .style(Button::custom(SettingsButtonStyle)),
+                    button(text("Kill").size(12))
+                        .padding([4, 8])
+                        .on_press(Message::KillProcess)
+                        .style(Button::custom(SettingsButtonStyle)),
+                )
+                .spacing(8)
+                .align_items(Alignment::Center)
+                .into()
+            });
+
+        Column::with_children(
+            vec![Some(header.into()), Some(list.into()), actions]
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>(),
+        )
+        .spacing(8)
+        .into()
+    }
+
+    pub fn menu_view(&self, config: &SystemModuleConfig) -> Element<Message> {
+        let unit = config.temperature_unit;
+
+        let sensors = self
+            .components
+            .iter()
+            .filter(|c| c.temperature().is_finite())
+            .map(|c| {
+                let selected = is_selected_sensor(c, &config.temperature_sensor);
+                let critical = c.critical().filter(|t| t.is_finite());
+                let temperature = c.temperature();
+
+                container(
+                    row!(
+                        text(c.label()).size(12).width(Length::Fill),
+                        text(format_temperature(temperature, unit)).size(12),
+                        text(
+                            critical
+                                .map(|t| format!("crit {}", format_temperature(t, unit)))
+                                .unwrap_or_default()
+                        )
+                        .size(10)
+                        .width(Length::Fixed(70.)),
+                    )
+                    .spacing(8)
+                    .align_items(Alignment::Center),
+                )
+                .style(move |theme: &Theme| container::Appearance {
+                    text_color: if critical.is_some_and(|critical| temperature >= critical) {
+                        Some(theme.palette().danger)
+                    } else if selected {
+                        Some(theme.palette().primary)
+                    } else {
+                        None
+                    },
+                    ..Default::default()
+                })
+                .into()
+            })
+            .collect::<Vec<Element<'_, _, _>>>();
+
+        column!(
+            self.history_view(config),
+            horizontal_rule(1),
+            self.processes_view(),
+            horizontal_rule(1),
+            text("Sensors").size(12),
+            Column::with_children(sensors).spacing(4),
+        )
+        .spacing(8)
+        .padding(16)
+        .width(400)
+        .into()
+    }
+
     pub fn subscription(&self) -> iced::Subscription<Message> {
-        iced::time::every(Duration::from_secs(5)).map(|_| Message::Update)
+        // each metric is refreshed on its own interval, checked every second
+        iced::time::every(Duration::from_secs(1)).map(Message::Update)
     }
 }
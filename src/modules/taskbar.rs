@@ -0,0 +1,273 @@
+use crate::{
+    components::icons::{app_icon, icon, Icons},
+    config::TaskbarModuleConfig,
+    menu::{Menu, MenuType},
+    style::GhostButtonStyle,
+    utils::{toplevels::ToplevelCommand, Commander},
+};
+use iced::{
+    theme::Button,
+    widget::{button, column, container, mouse_area, row, text, Column},
+    Alignment, Element, Length, Theme,
+};
+use itertools::Itertools;
+use std::time::Duration;
+
+// time left to move the pointer from the taskbar to the group menu
+const MENU_CLOSE_DELAY: Duration = Duration::from_millis(300);
+
+#[derive(Debug, Clone, Default)]
+pub struct Toplevel {
+    pub id: String,
+    pub app_id: String,
+    pub title: String,
+    pub active: bool,
+}
+
+#[derive(Debug, Clone)]
+pub enum TaskbarMessage {
+    Toplevels(Vec<Toplevel>),
+    FocusGroup(String),
+    CloseGroup(String),
+    OpenGroupMenu(String),
+    LeaveGroupMenu,
+    EnterGroupMenu,
+    CloseGroupMenu(u64),
+    Activate(String),
+    Close(String),
+}
+
+pub struct Taskbar {
+    commander: Commander<ToplevelCommand>,
+    toplevels: Vec<Toplevel>,
+    menu_group: Option<String>,
+    close_generation: u64,
+}
+
+impl Taskbar {
+    pub fn new() -> Self {
+        Self {
+            commander: Commander::new(),
+            toplevels: Vec::new(),
+            menu_group: None,
+            close_generation: 0,
+        }
+    }
+
+    fn groups(&self) -> Vec<(String, Vec<&Toplevel>)> {
+        self.toplevels
+            .iter()
+            .into_group_map_by(|t| t.app_id.clone())
+            .into_iter()
+            .sorted_by(|(a, _), (b, _)| a.cmp(b))
+            .collect()
+    }
+
+    fn group(&self, app_id: &str) -> Vec<&Toplevel> {
+        self.toplevels
+            .iter()
+            .filter(|t| t.app_id == app_id)
+            .collect()
+    }
+
+    pub fn update(
+        &mut self,
+        message: TaskbarMessage,
+        menu: &mut Menu,
+    ) -> iced::Command<TaskbarMessage> {
+        match message {
+            TaskbarMessage::Toplevels(toplevels) => {
+                self.toplevels = toplevels;
+
+                if self
+                    .menu_group
+                    .as_ref()
+                    .is_some_and(|app_id| self.group(app_id).is_empty())
+                {
+                    self.menu_group = None;
+                    menu.close_if(MenuType::Taskbar)
+                } else {
+                    iced::Command::none()
+                }
+            }
+            TaskbarMessage::FocusGroup(app_id) => {
+                let group = self.group(&app_id);
+                // cycle through the group windows starting from the active one
+                let next = group
+                    .iter()
+                    .position(|t| t.active)
+                    .map_or(0, |i| (i + 1) % group.len());
+
+                if let Some(toplevel) = group.get(next) {
+                    let _ = self
+                        .commander
+                        .send(ToplevelCommand::Activate(toplevel.id.clone()));
+                }
+
+                iced::Command::none()
+            }
+            TaskbarMessage::CloseGroup(app_id) => {
+                let group = self.group(&app_id);
+
+                if let Some(toplevel) = group.iter().find(|t| t.active).or(group.first()) {
+                    let _ = self
+                        .commander
+                        .send(ToplevelCommand::Close(toplevel.id.clone()));
+                }
+
+                iced::Command::none()
+            }
+            TaskbarMessage::OpenGroupMenu(app_id) => {
+                // hovering must not replace a menu opened by another module
+                match menu.get_menu_type() {
+                    None => {
+                        self.close_generation += 1;
+                        self.menu_group = Some(app_id);
+
+                        menu.toggle(MenuType::Taskbar)
+                    }
+                    Some(MenuType::Taskbar) => {
+                        self.close_generation += 1;
+                        self.menu_group = Some(app_id);
+
+                        iced::Command::none()
+                    }
+                    Some(_) => iced::Command::none(),
+                }
+            }
+            TaskbarMessage::LeaveGroupMenu => {
+                self.close_generation += 1;
+
+                let generation = self.close_generation;
+                iced::Command::perform(tokio::time::sleep(MENU_CLOSE_DELAY), move |_| {
+                    TaskbarMessage::CloseGroupMenu(generation)
+                })
+            }
+            TaskbarMessage::EnterGroupMenu => {
+                self.close_generation += 1;
+
+                iced::Command::none()
+            }
+            TaskbarMessage::CloseGroupMenu(generation) => {
+                if generation != self.close_generation {
+                    return iced::Command::none();
+                }
+
+                self.menu_group = None;
+                menu.close_if(MenuType::Taskbar)
+            }
+            TaskbarMessage::Activate(id) => {
+                let _ = self.commander.send(ToplevelCommand::Activate(id));
+                self.menu_group = None;
+
+                menu.close_if(MenuType::Taskbar)
+            }
+            TaskbarMessage::Close(id) => {
+                let _ = self.commander.send(ToplevelCommand::Close(id));
+
+                iced::Command::none()
+            }
+        }
+    }
+
+    pub fn view(&self, config: &TaskbarModuleConfig) -> Option<Element<TaskbarMessage>> {
+        if config.disabled || self.toplevels.is_empty() {
+            return None;
+        }
+
+        Some(
+            Column::with_children(
+                self.groups()
+                    .into_iter()
+                    .map(|(app_id, toplevels)| {
+                        let active = toplevels.iter().any(|t| t.active);
+                        let count = toplevels.len();
+
+                        mouse_area(
+                            container(
+                                column!(
+                                    icon(app_icon(&app_id)),
+                                    text(if count > 1 {
+                                        count.to_string()
+                                    } else {
+                                        String::new()
+                                    })
+                                    .size(8)
+                                )
+                                .align_items(Alignment::Center),
+                            )
+                            .padding([2, 4])
+                            .style(move |theme: &Theme| {
+                                container::Appearance {
+                                    text_color: if active {
+                                        Some(theme.palette().primary)
+                                    } else {
+                                        None
+                                    },
+                                    ..Default::default()
+                                }
+                            }),
+                        )
+                        .on_release(TaskbarMessage::FocusGroup(app_id.clone()))
+                        .on_middle_release(TaskbarMessage::CloseGroup(app_id.clone()))
+                        .on_enter(TaskbarMessage::OpenGroupMenu(app_id))
+                        .on_exit(TaskbarMessage::LeaveGroupMenu)
+                        .into()
+                    })
+                    .collect::<Vec<Element<'_, _, _>>>(),
+            )
+            .align_items(Alignment::Center)
+            .spacing(4)
+            .into(),
+        )
+    }
+
+    pub fn menu_view(&self) -> Element<TaskbarMessage> {
+        let toplevels = self
+            .menu_group
+            .as_ref()
+            .map(|app_id| self.group(app_id))
+            .unwrap_or_default();
+
+        mouse_area(
+            Column::with_children(
+                toplevels
+                    .into_iter()
+                    .map(|t| {
+                        row!(
+                            button(
+                                row!(
+                                    icon(app_icon(&t.app_id)),
+                                    text(&t.title).size(12).width(Length::Fill)
+                                )
+                                .spacing(8)
+                                .align_items(Alignment::Center)
+                            )
+                            .padding([4, 8])
+                            .width(Length::Fill)
+                            .on_press(TaskbarMessage::Activate(t.id.clone()))
+                            .style(Button::custom(GhostButtonStyle)),
+                            button(icon(Icons::Close))
+                                .padding([4, 8])
+                                .on_press(TaskbarMessage::Close(t.id.clone()))
+                                .style(Button::custom(GhostButtonStyle))
+                        )
+                        .align_items(Alignment::Center)
+                        .spacing(4)
+                        .into()
+                    })
+                    .collect::<Vec<Element<'_, _, _>>>(),
+            )
+            .spacing(4)
+            .padding(16)
+            .width(300),
+        )
+        .on_enter(TaskbarMessage::EnterGroupMenu)
+        .on_exit(TaskbarMessage::LeaveGroupMenu)
+        .into()
+    }
+
+    pub fn subscription(&self) -> iced::Subscription<TaskbarMessage> {
+        crate::utils::toplevels::subscription(self.commander.give_receiver())
+    }
+}
@@ -1,60 +1,326 @@
-use hyprland::{data::Client, event_listener::EventListener, shared::HyprDataActiveOptional};
+use hyprland::{
+    data::{Client, Monitors},
+    dispatch::{Dispatch, DispatchType},
+    event_listener::EventListener,
+    shared::{HyprData, HyprDataActiveOptional, HyprDataVec},
+};
 use iced::{
-    widget::{container, text},
-    Element,
+    theme::Button,
+    widget::{button, column, container, horizontal_rule, row, text, Column, Row},
+    Alignment, Element, Length,
 };
+use log::error;
 use std::cell::RefCell;
 
-use crate::style::header_pills;
+use crate::{
+    components::icons::{app_icon, icon, Icons},
+    config::{TitleModuleConfig, TitleRenderMode},
+    menu::{Menu, MenuType},
+    style::{GhostButtonStyle, HeaderButtonStyle, SettingsButtonStyle},
+};
+
+const MOVE_TO_WORKSPACES: i32 = 10;
+
+#[derive(Debug, Clone)]
+pub struct ActiveWindow {
+    pub address: String,
+    pub class: String,
+    pub title: String,
+    pub pid: i32,
+    pub workspace: String,
+    pub floating: bool,
+    pub pinned: bool,
+    pub fullscreen: bool,
+}
+
+impl From<Client> for ActiveWindow {
+    fn from(client: Client) -> Self {
+        Self {
+            address: client.address.to_string(),
+            class: client.class,
+            title: client.title,
+            pid: client.pid,
+            workspace: client.workspace.name,
+            floating: client.floating,
+            pinned: client.pinned,
+            fullscreen: client.fullscreen,
+        }
+    }
+}
+
+fn get_active_window() -> Option<ActiveWindow> {
+    Client::get_active().ok().flatten().map(ActiveWindow::from)
+}
+
+fn get_monitors() -> Vec<String> {
+    Monitors::get()
+        .map(|m| m.to_vec().into_iter().map(|m| m.name).collect())
+        .unwrap_or_default()
+}
+
+fn dispatch(dispatcher: &str, arg: &str) {
+    if let Err(e) = Dispatch::call(DispatchType::Custom(dispatcher, arg)) {
+        error!("failed to dispatch {} {}: {:?}", dispatcher, arg, e);
+    }
+}
 
 pub struct Title {
-    value: Option<String>,
+    value: Option<ActiveWindow>,
+    monitors: Vec<String>,
 }
 
 #[derive(Debug, Clone)]
 pub enum Message {
-    TitleChanged(Option<String>),
+    TitleChanged(Option<ActiveWindow>),
+    ToggleMenu,
+    Action(WindowAction),
+}
+
+#[derive(Debug, Clone)]
+pub enum WindowAction {
+    Kill,
+    ToggleFloating,
+    TogglePin,
+    ToggleFullscreen,
+    MoveToWorkspace(i32),
+    MoveToMonitor(String),
+}
+
+impl WindowAction {
+    fn dispatch(&self, address: &str) {
+        let window = format!("address:{}", address);
+        match self {
+            WindowAction::Kill => dispatch("closewindow", &window),
+            WindowAction::ToggleFloating => dispatch("togglefloating", &window),
+            WindowAction::TogglePin => dispatch("pin", &window),
+            WindowAction::ToggleFullscreen => {
+                // fullscreen only acts on the focused window
+                dispatch("focuswindow", &window);
+                dispatch("fullscreen", "0");
+            }
+            WindowAction::MoveToWorkspace(id) => {
+                dispatch("movetoworkspacesilent", &format!("{},{}", id, window))
+            }
+            WindowAction::MoveToMonitor(monitor) => {
+                dispatch("focuswindow", &window);
+                dispatch("movewindow", &format!("mon:{}", monitor));
+            }
+        }
+    }
+}
+
+fn truncate_title(value: String, truncate_title_after_length: u32) -> String {
+    let length = value.chars().count();
+
+    if length > truncate_title_after_length as usize {
+        let split = truncate_title_after_length as usize / 2;
+        let first_part = value.chars().take(split).collect::<String>();
+        let last_part = value.chars().skip(length - split).collect::<String>();
+        format!("{}...{}", first_part, last_part)
+    } else {
+        value
+    }
 }
 
 impl Title {
     pub fn new() -> Self {
-        let init = Client::get_active()
-            .ok()
-            .and_then(|w| w.map(|w| w.initial_title));
-
-        Self { value: init }
+        Self {
+            value: get_active_window(),
+            monitors: Vec::new(),
+        }
     }
 
-    pub fn update(&mut self, message: Message, truncate_title_after_length: u32) {
+    pub fn update(&mut self, message: Message, menu: &mut Menu) -> iced::Command<Message> {
         match message {
             Message::TitleChanged(value) => {
-                if let Some(value) = value {
-                    let length = value.len();
-
-                    self.value = Some(if length > truncate_title_after_length as usize {
-                        let split = truncate_title_after_length as usize / 2;
-                        let first_part = &value[0..split];
-                        let last_part = &value[length - split..length];
-                        format!("{}...{}", first_part, last_part)
-                    } else {
-                        value
-                    });
+                self.value = value;
+
+                if self.value.is_none() {
+                    menu.close_if(MenuType::Title)
                 } else {
-                    self.value = None;
+                    iced::Command::none()
+                }
+            }
+            Message::ToggleMenu => {
+                self.monitors = get_monitors();
+                menu.toggle(MenuType::Title)
+            }
+            Message::Action(action) => {
+                if let Some(window) = &self.value {
+                    action.dispatch(&window.address);
                 }
+
+                menu.close_if(MenuType::Title)
             }
         }
     }
 
-    pub fn view(&self) -> Option<Element<Message>> {
-        self.value.as_ref().map(|value| {
-            container(text(value).size(12))
-                .padding([2, 7])
-                .style(header_pills)
-                .into()
+    pub fn view(
+        &self,
+        config: &TitleModuleConfig,
+        truncate_title_after_length: u32,
+    ) -> Option<Element<Message>> {
+        if config.disabled {
+            return None;
+        }
+
+        self.value.as_ref().map(|window| {
+            let title: Element<Message> = match config.mode {
+                TitleRenderMode::Vertical => Column::with_children(
+                    truncate_title(
+                        window.title.clone(),
+                        config.vertical_max_length.min(truncate_title_after_length),
+                    )
+                    .chars()
+                    .map(|c| text(c).size(12).into())
+                    .collect::<Vec<Element<'_, _, _>>>(),
+                )
+                .align_items(Alignment::Center)
+                .into(),
+                TitleRenderMode::Horizontal => text(truncate_title(
+                    window.title.clone(),
+                    truncate_title_after_length,
+                ))
+                .size(12)
+                .into(),
+            };
+
+            button(
+                Column::with_children(
+                    vec![
+                        if config.show_class_icon {
+                            Some(icon(app_icon(&window.class)).into())
+                        } else {
+                            None
+                        },
+                        Some(title),
+                    ]
+                    .into_iter()
+                    .flatten()
+                    .collect::<Vec<_>>(),
+                )
+                .align_items(Alignment::Center)
+                .spacing(4),
+            )
+            .padding(match config.mode {
+                TitleRenderMode::Vertical => [7, 2],
+                TitleRenderMode::Horizontal => [2, 7],
+            })
+            .style(Button::custom(HeaderButtonStyle::Full))
+            .on_press(Message::ToggleMenu)
+            .into()
         })
     }
 
+    pub fn menu_view(&self) -> Element<Message> {
+        let Some(window) = &self.value else {
+            return container(text("No active window")).padding(16).into();
+        };
+
+        let action_button = |icon_type: Icons, label: &str, active: bool, action: WindowAction| {
+            button(
+                row!(
+                    icon(icon_type),
+                    text(label.to_string()).width(Length::Fill),
+                    text(if active { "on" } else { "" }).size(10)
+                )
+                .spacing(16)
+                .align_items(Alignment::Center),
+            )
+            .padding([4, 12])
+            .on_press(Message::Action(action))
+            .width(Length::Fill)
+            .style(Button::custom(GhostButtonStyle))
+        };
+
+        let workspaces = (1..=MOVE_TO_WORKSPACES)
+            .collect::<Vec<_>>()
+            .chunks(5)
+            .map(|ids| {
+                Row::with_children(
+                    ids.iter()
+                        .map(|id| {
+                            button(
+                                text(id)
+                                    .width(Length::Fill)
+                                    .horizontal_alignment(iced::alignment::Horizontal::Center),
+                            )
+                            .padding([4, 8])
+                            .width(Length::Fill)
+                            .on_press(Message::Action(WindowAction::MoveToWorkspace(*id)))
+                            .style(Button::custom(SettingsButtonStyle))
+                            .into()
+                        })
+                        .collect::<Vec<Element<'_, _, _>>>(),
+                )
+                .spacing(4)
+                .into()
+            })
+            .collect::<Vec<Element<'_, _, _>>>();
+
+        let monitors = self
+            .monitors
+            .iter()
+            .map(|monitor| {
+                button(text(monitor))
+                    .padding([4, 12])
+                    .width(Length::Fill)
+                    .on_press(Message::Action(WindowAction::MoveToMonitor(
+                        monitor.clone(),
+                    )))
+                    .style(Button::custom(GhostButtonStyle))
+                    .into()
+            })
+            .collect::<Vec<Element<'_, _, _>>>();
+
+        column!(
+            row!(
+                icon(app_icon(&window.class)).size(20),
+                column!(
+                    text(&window.class).size(14),
+                    text(format!(
+                        "PID {} · workspace {}",
+                        window.pid, window.workspace
+                    ))
+                    .size(10),
+                )
+                .spacing(4)
+            )
+            .spacing(12)
+            .align_items(Alignment::Center),
+            horizontal_rule(1),
+            action_button(Icons::Close, "Close", false, WindowAction::Kill),
+            action_button(
+                Icons::WindowFloating,
+                "Floating",
+                window.floating,
+                WindowAction::ToggleFloating
+            ),
+            action_button(
+                Icons::WindowPin,
+                "Pin",
+                window.pinned,
+                WindowAction::TogglePin
+            ),
+            action_button(
+                Icons::WindowFullscreen,
+                "Fullscreen",
+                window.fullscreen,
+                WindowAction::ToggleFullscreen
+            ),
+            horizontal_rule(1),
+            text("Move to workspace").size(12),
+            Column::with_children(workspaces).spacing(4),
+            horizontal_rule(1),
+            text("Move to monitor").size(12),
+            Column::with_children(monitors).spacing(4),
+        )
+        .spacing(8)
+        .padding(16)
+        .width(250)
+        .into()
+    }
+
     pub fn subscription(&self) -> iced::Subscription<Message> {
         iced::subscription::channel("title-listener", 10, |output| async move {
             let output = RefCell::new(output);
@@ -65,7 +331,27 @@ impl Title {
                 move |e| {
                     let mut output = output.borrow_mut();
                     output
-                        .try_send(Message::TitleChanged(e.map(|e| e.window_title)))
+                        .try_send(Message::TitleChanged(e.and_then(|_| get_active_window())))
+                        .unwrap();
+                }
+            });
+
+            event_listener.add_float_state_handler({
+                let output = output.clone();
+                move |_| {
+                    let mut output = output.borrow_mut();
+                    output
+                        .try_send(Message::TitleChanged(get_active_window()))
+                        .unwrap();
+                }
+            });
+
+            event_listener.add_fullscreen_state_change_handler({
+                let output = output.clone();
+                move |_| {
+                    let mut output = output.borrow_mut();
+                    output
+                        .try_send(Message::TitleChanged(get_active_window()))
                         .unwrap();
                 }
             });
@@ -9,60 +9,86 @@ use iced::{
 };
 use std::cell::RefCell;
 
+use crate::config::{WorkspaceVisibilityMode, WorkspacesModuleConfig};
+
 #[derive(Debug, Clone)]
 pub struct Workspace {
     pub id: i32,
     pub monitor: Option<usize>,
+    pub monitor_name: Option<String>,
     pub active: bool,
     pub windows: u16,
 }
 
-const MONITOR: [&str; 3] = ["eDP-1", "DP-1", "DP-2"];
+impl Workspace {
+    fn placeholder(id: i32, monitor: Option<usize>, monitor_name: Option<String>) -> Self {
+        Self {
+            id,
+            monitor,
+            monitor_name,
+            active: false,
+            windows: 0,
+        }
+    }
+}
+
+fn get_monitors() -> Vec<String> {
+    let mut monitors = hyprland::data::Monitors::get()
+        .map(|m| m.to_vec())
+        .unwrap_or_default();
+
+    monitors.sort_by_key(|m| m.id);
+
+    monitors.into_iter().map(|m| m.name).collect()
+}
 
 fn get_workspaces() -> Vec<Workspace> {
     let active = hyprland::data::Workspace::get_active().unwrap();
+    let monitors = get_monitors();
     let mut workspaces = hyprland::data::Workspaces::get()
         .map(|w| w.to_vec())
         .unwrap_or_default();
 
     workspaces.sort_by_key(|w| w.id);
 
-    let mut current: usize = 1;
-    let s = workspaces
-        .iter()
-        .flat_map(|w| {
-            let missing: usize = w.id as usize - current;
-            let mut res = Vec::with_capacity(missing + 1);
-            for i in 0..missing {
-                res.push(Workspace {
-                    id: (current + i) as i32,
-                    monitor: None,
-                    active: false,
-                    windows: 0,
-                });
-            }
-            current += missing + 1;
-            res.push(Workspace {
-                id: w.id,
-                monitor: MONITOR.iter().position(|m| w.monitor == *m),
-                active: w.id == active.id,
-                windows: w.windows,
-            });
-
-            res
+    workspaces
+        .into_iter()
+        // special workspaces have negative ids
+        .filter(|w| w.id > 0)
+        .map(|w| Workspace {
+            id: w.id,
+            monitor: monitors.iter().position(|m| w.monitor == *m),
+            active: w.id == active.id,
+            windows: w.windows,
+            monitor_name: Some(w.monitor),
         })
-        .collect::<Vec<Workspace>>();
+        .collect()
+}
 
-    s
+/// The output hosting the bar layer surface, only the bar uses the `ashell` namespace
+fn get_bar_output() -> Option<String> {
+    hyprland::data::Layers::get()
+        .ok()?
+        .into_iter()
+        .find_map(|(monitor, display)| {
+            display
+                .levels
+                .values()
+                .flatten()
+                .any(|layer| layer.namespace == "ashell")
+                .then_some(monitor)
+        })
 }
 
 pub struct Workspaces {
     workspaces: Vec<Workspace>,
+    monitors: Vec<String>,
+    output: Option<String>,
 }
 
 #[derive(Debug, Clone)]
 pub enum Message {
-    WorkspacesChanged(Vec<Workspace>),
+    WorkspacesChanged(Vec<Workspace>, Vec<String>),
     ChangeWorkspace(i32),
 }
 
@@ -70,13 +96,20 @@ impl Workspaces {
     pub fn new() -> Self {
         Self {
             workspaces: get_workspaces(),
+            monitors: get_monitors(),
+            // the bar surface could be not mapped yet, in that case it's resolved later
+            output: get_bar_output(),
         }
     }
 
     pub fn update(&mut self, message: Message) {
         match message {
-            Message::WorkspacesChanged(workspaces) => {
+            Message::WorkspacesChanged(workspaces, monitors) => {
                 self.workspaces = workspaces;
+                self.monitors = monitors;
+                if self.output.is_none() {
+                    self.output = get_bar_output();
+                }
             }
             Message::ChangeWorkspace(id) => {
                 hyprland::dispatch::Dispatch::call(hyprland::dispatch::DispatchType::Workspace(
@@ -87,10 +120,84 @@ impl Workspaces {
         }
     }
 
-    pub fn view(&self, workspace_colors: &[HexColor]) -> Element<Message> {
-        Column::with_children(
-            self.workspaces
+    fn visible_workspaces(&self, config: &WorkspacesModuleConfig) -> Vec<Workspace> {
+        let output = config.output.as_ref().or(self.output.as_ref());
+        let monitor_specific = config.visibility_mode == WorkspaceVisibilityMode::MonitorSpecific;
+
+        let monitor_index = |name: &str| self.monitors.iter().position(|m| m == name);
+        let assigned_monitor = |id: i32| {
+            config
+                .monitor_workspaces
                 .iter()
+                .find(|(_, ids)| ids.contains(&id))
+                .map(|(name, _)| name.clone())
+        };
+
+        let mut workspaces = self
+            .workspaces
+            .iter()
+            .filter(|w| !monitor_specific || output.is_none() || w.monitor_name.as_ref() == output)
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let mut persistent: Vec<(i32, Option<String>)> = if monitor_specific {
+            match output.and_then(|o| config.monitor_workspaces.get(o).map(|ids| (o, ids))) {
+                Some((output, ids)) => ids.iter().map(|id| (*id, Some(output.clone()))).collect(),
+                None => (1..=config.persistent_workspaces as i32)
+                    .filter(|id| assigned_monitor(*id).is_none())
+                    .map(|id| (id, output.cloned()))
+                    .collect(),
+            }
+        } else {
+            (1..=config.persistent_workspaces as i32)
+                .map(|id| (id, assigned_monitor(id)))
+                .chain(
+                    config
+                        .monitor_workspaces
+                        .iter()
+                        .flat_map(|(name, ids)| ids.iter().map(|id| (*id, Some(name.clone())))),
+                )
+                .collect()
+        };
+
+        // without any persistent slot fill the gaps between the shown workspaces,
+        // on a single monitor the ids before its first workspace belong to the others
+        if persistent.is_empty() {
+            let min = if monitor_specific {
+                workspaces.iter().map(|w| w.id).min().unwrap_or(1)
+            } else {
+                1
+            };
+            let max = workspaces.iter().map(|w| w.id).max().unwrap_or_default();
+            let monitor_name = output.filter(|_| monitor_specific).cloned();
+            persistent = (min..=max).map(|id| (id, monitor_name.clone())).collect();
+        }
+
+        for (id, monitor_name) in persistent {
+            // an existing workspace filtered out above lives on another output
+            if !workspaces.iter().any(|w| w.id == id) && !self.workspaces.iter().any(|w| w.id == id)
+            {
+                workspaces.push(Workspace::placeholder(
+                    id,
+                    monitor_name.as_deref().and_then(monitor_index),
+                    monitor_name,
+                ));
+            }
+        }
+
+        workspaces.sort_by_key(|w| w.id);
+
+        workspaces
+    }
+
+    pub fn view(
+        &self,
+        config: &WorkspacesModuleConfig,
+        workspace_colors: &[HexColor],
+    ) -> Element<Message> {
+        Column::with_children(
+            self.visible_workspaces(config)
+                .into_iter()
                 .map(|w| {
                     let empty = w.windows == 0;
                     let monitor = w.monitor;
@@ -155,7 +262,7 @@ impl Workspaces {
                 move |_| {
                     let mut output = output.borrow_mut();
                     output
-                        .try_send(Message::WorkspacesChanged(get_workspaces()))
+                        .try_send(Message::WorkspacesChanged(get_workspaces(), get_monitors()))
                         .expect("error getting workspaces: workspace added event");
                 }
             });
@@ -165,7 +272,7 @@ impl Workspaces {
                 move |_| {
                     let mut output = output.borrow_mut();
                     output
-                        .try_send(Message::WorkspacesChanged(get_workspaces()))
+                        .try_send(Message::WorkspacesChanged(get_workspaces(), get_monitors()))
                         .expect("error getting workspaces: workspace change event");
                 }
             });
@@ -175,7 +282,7 @@ impl Workspaces {
                 move |_| {
                     let mut output = output.borrow_mut();
                     output
-                        .try_send(Message::WorkspacesChanged(get_workspaces()))
+                        .try_send(Message::WorkspacesChanged(get_workspaces(), get_monitors()))
                         .expect("error getting workspaces: workspace destroy event");
                 }
             });
@@ -185,7 +292,7 @@ impl Workspaces {
                 move |_| {
                     let mut output = output.borrow_mut();
                     output
-                        .try_send(Message::WorkspacesChanged(get_workspaces()))
+                        .try_send(Message::WorkspacesChanged(get_workspaces(), get_monitors()))
                         .expect("error getting workspaces: workspace moved event");
                 }
             });
@@ -195,7 +302,7 @@ impl Workspaces {
                 move |_| {
                     let mut output = output.borrow_mut();
                     output
-                        .try_send(Message::WorkspacesChanged(get_workspaces()))
+                        .try_send(Message::WorkspacesChanged(get_workspaces(), get_monitors()))
                         .expect("error getting workspaces: window close event");
                 }
             });
@@ -205,7 +312,7 @@ impl Workspaces {
                 move |_| {
                     let mut output = output.borrow_mut();
                     output
-                        .try_send(Message::WorkspacesChanged(get_workspaces()))
+                        .try_send(Message::WorkspacesChanged(get_workspaces(), get_monitors()))
                         .expect("error getting workspaces: window open event");
                 }
             });
@@ -215,7 +322,7 @@ impl Workspaces {
                 move |_| {
                     let mut output = output.borrow_mut();
                     output
-                        .try_send(Message::WorkspacesChanged(get_workspaces()))
+                        .try_send(Message::WorkspacesChanged(get_workspaces(), get_monitors()))
                         .expect("error getting workspaces: window moved event");
                 }
             });
@@ -225,7 +332,7 @@ impl Workspaces {
                 move |_| {
                     let mut output = output.borrow_mut();
                     output
-                        .try_send(Message::WorkspacesChanged(get_workspaces()))
+                        .try_send(Message::WorkspacesChanged(get_workspaces(), get_monitors()))
                         .expect("error getting workspaces: active monitor change event");
                 }
             });
@@ -0,0 +1,191 @@
+use crate::{
+    components::icons::{icon, Icons},
+    config::OsdConfig,
+};
+use iced::wayland::actions::layer_surface::{IcedMargin, SctkLayerSurfaceSettings};
+use iced::wayland::layer_surface::{Anchor, KeyboardInteractivity, Layer};
+use iced::widget::{container, progress_bar, row, text};
+use iced::{window::Id, Alignment, Border, Command, Element, Length, Theme};
+use std::time::Duration;
+
+const WIDTH: u32 = 300;
+const HEIGHT: u32 = 56;
+
+fn open_osd<Message>() -> (Id, Command<Message>) {
+    let id = Id::unique();
+
+    (
+        id,
+        iced::wayland::layer_surface::get_layer_surface(SctkLayerSurfaceSettings {
+            id,
+            keyboard_interactivity: KeyboardInteractivity::None,
+            namespace: "ashell-osd".into(),
+            layer: Layer::Overlay,
+            // the OSD is only informative, clicks go through it
+            pointer_interactivity: false,
+            size: Some((Some(WIDTH), Some(HEIGHT))),
+            anchor: Anchor::BOTTOM,
+            margin: IcedMargin {
+                bottom: 64,
+                ..Default::default()
+            },
+            ..Default::default()
+        }),
+    )
+}
+
+fn close_osd<Message>(id: Id) -> Command<Message> {
+    iced::wayland::layer_surface::destroy_layer_surface(id)
+}
+
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum OsdKind {
+    Volume,
+    Microphone,
+    Brightness,
+}
+
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub struct OsdLevel {
+    pub kind: OsdKind,
+    /// Device the level belongs to, a level change is shown only for the same device
+    pub device: String,
+    /// Percentage of the level
+    pub value: i32,
+    pub muted: bool,
+}
+
+impl OsdLevel {
+    fn icon(&self) -> Icons {
+        match self.kind {
+            OsdKind::Volume => {
+                if self.muted || self.value == 0 {
+                    Icons::Speaker0
+                } else if self.value > 66 {
+                    Icons::Speaker3
+                } else if self.value > 33 {
+                    Icons::Speaker2
+                } else {
+                    Icons::Speaker1
+                }
+            }
+            OsdKind::Microphone => {
+                if self.muted {
+                    Icons::Mic0
+                } else {
+                    Icons::Mic1
+                }
+            }
+            OsdKind::Brightness => Icons::Brightness,
+        }
+    }
+
+    fn enabled(&self, config: &OsdConfig) -> bool {
+        match self.kind {
+            OsdKind::Volume => config.volume,
+            OsdKind::Microphone => config.microphone,
+            OsdKind::Brightness => config.brightness,
+        }
+    }
+}
+
+pub struct Osd {
+    id: Option<Id>,
+    level: Option<OsdLevel>,
+    /// Incremented on each show, only the last timeout hides the OSD
+    generation: u64,
+}
+
+impl Osd {
+    pub fn init() -> Self {
+        Self {
+            id: None,
+            level: None,
+            generation: 0,
+        }
+    }
+
+    pub fn show<Msg: 'static>(
+        &mut self,
+        level: OsdLevel,
+        config: &OsdConfig,
+        on_timeout: fn(u64) -> Msg,
+    ) -> Command<Msg> {
+        if !level.enabled(config) {
+            return Command::none();
+        }
+
+        self.level = Some(level);
+        self.generation += 1;
+
+        let generation = self.generation;
+        let hide = Command::perform(
+            tokio::time::sleep(Duration::from_millis(config.timeout)),
+            move |_| on_timeout(generation),
+        );
+
+        if self.id.is_some() {
+            hide
+        } else {
+            let (id, cmd) = open_osd();
+            self.id = Some(id);
+
+            Command::batch(vec![cmd, hide])
+        }
+    }
+
+    pub fn hide<Msg>(&mut self, generation: u64) -> Command<Msg> {
+        if generation != self.generation {
+            return Command::none();
+        }
+
+        self.level = None;
+        if let Some(id) = self.id.take() {
+            close_osd(id)
+        } else {
+            Command::none()
+        }
+    }
+
+    pub fn get_id(&self) -> Option<Id> {
+        self.id
+    }
+
+    pub fn view<'a, Msg: 'a>(&self) -> Element<'a, Msg> {
+        let Some(level) = &self.level else {
+            return row!().into();
+        };
+
+        container(
+            row!(
+                icon(level.icon()).size(20),
+                progress_bar(0.0..=100.0, level.value as f32)
+                    .width(Length::Fill)
+                    .height(Length::Fixed(6.)),
+                text(if level.muted {
+                    "Muted".to_string()
+                } else {
+                    format!("{}%", level.value)
+                })
+                .size(12)
+                .width(Length::Fixed(40.)),
+            )
+            .spacing(12)
+            .align_items(Alignment::Center),
+        )
+        .padding([0, 16])
+        .center_y()
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .style(|theme: &Theme| container::Appearance {
+            background: Some(theme.palette().background.into()),
+            border: Border {
+                color: iced::Color::WHITE,
+                width: 2.,
+                radius: 10.0.into(),
+            },
+            ..Default::default()
+        })
+        .into()
+    }
+}
@@ -1,76 +1,111 @@
 extern crate libpulse_binding as pulse;
 
-use crate::{components::icons::Icons, modules::settings::audio::AudioMessage};
+use crate::{
+    components::icons::Icons,
+    config::{AudioBackend, AudioDeviceRule, AudioDevicesConfig, AudioPortType},
+    modules::settings::audio::AudioMessage,
+    utils::pipewire::{Direction, Node, Pipewire, PipewireCommand, PipewireState},
+};
 use iced::{futures::SinkExt, Subscription};
 use libpulse_binding::{
     context::Context,
     mainloop::standard::{IterateResult, Mainloop},
     proplist::Proplist,
 };
-use log::{error, trace};
+use log::{error, trace, warn};
 use pulse::{
     callbacks::ListResult,
     context::{
-        introspect::{SinkInfo, SourceInfo},
+        introspect::{CardInfo, SinkInfo, SinkInputInfo, SourceInfo, SourceOutputInfo},
         subscribe::InterestMaskSet,
         FlagSet,
     },
-    def::{DevicePortType, PortAvailable, SourceState},
+    def::{BufferAttr, DevicePortType, PortAvailable, SourceState},
     operation::{Operation, State},
+    sample::CHANNELS_MAX,
+    stream::{FlagSet as StreamFlagSet, PeekResult, Stream},
     volume::ChannelVolumes,
 };
 use std::{
-    cell::RefCell,
+    cell::{Cell, RefCell},
+    error::Error,
     ops::{Deref, DerefMut},
     rc::Rc,
     thread,
+    time::Duration,
 };
 
-fn init(name: &str) -> (Rc<RefCell<Mainloop>>, Rc<RefCell<Context>>) {
-    let mut proplist = Proplist::new().unwrap();
+/// Name of the noise suppression filter source
+pub const NOISE_SUPPRESSION_SOURCE: &str = "ashell_noise_suppression";
+
+/// Application name of the peak detection stream, hidden from the mixer and the privacy module
+pub const SOURCE_METER_NAME: &str = "ashell-source-meter";
+
+/// Delay before restarting the audio listener when the sound server goes away
+const LISTENER_RETRY_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Filter sources don't have ports, a fake one is used to show them like the other sources
+fn noise_suppression_port() -> Port {
+    Port {
+        name: "noise-suppression".to_string(),
+        description: "Noise suppression".to_string(),
+        device_type: DeviceType::Headset,
+        active: true,
+    }
+}
+
+type PulseConnection = (Rc<RefCell<Mainloop>>, Rc<RefCell<Context>>);
+
+fn init(name: &str) -> Result<PulseConnection, Box<dyn Error>> {
+    let mut proplist = Proplist::new().ok_or("Failed to create proplist")?;
     proplist
         .set_str(pulse::proplist::properties::APPLICATION_NAME, name)
-        .unwrap();
+        .map_err(|_| "Failed to set the application name")?;
 
     let mainloop = Rc::new(RefCell::new(
-        Mainloop::new().expect("Failed to create mainloop"),
+        Mainloop::new().ok_or("Failed to create mainloop")?,
     ));
 
     let context = Rc::new(RefCell::new(
         Context::new_with_proplist(mainloop.borrow().deref(), name, &proplist)
-            .expect("Failed to create new context"),
+            .ok_or("Failed to create new context")?,
     ));
 
-    context
-        .borrow_mut()
-        .connect(None, FlagSet::NOFLAGS, None)
-        .expect("Failed to connect context");
+    context.borrow_mut().connect(None, FlagSet::NOFLAGS, None)?;
 
     // Wait for context to be ready
     loop {
         match mainloop.borrow_mut().iterate(true) {
             IterateResult::Quit(_) | IterateResult::Err(_) => {
-                panic!("PulseAudio: iterate state was not success")
+                return Err("PulseAudio: iterate state was not success".into());
             }
-            IterateResult::Success(_) => {
-                if context.borrow().get_state() == pulse::context::State::Ready {
-                    break;
+            IterateResult::Success(_) => match context.borrow().get_state() {
+                pulse::context::State::Ready => break,
+                pulse::context::State::Failed | pulse::context::State::Terminated => {
+                    return Err("PulseAudio: failed to connect the context".into());
                 }
-            }
+                _ => {}
+            },
         }
     }
 
-    (mainloop, context)
+    Ok((mainloop, context))
+}
+
+fn is_connected(context: &Context) -> bool {
+    context.get_state() == pulse::context::State::Ready
 }
 
 fn wait_for_response<T: ?Sized>(mainloop: &mut Mainloop, operation: Operation<T>) {
     loop {
         match mainloop.iterate(true) {
             IterateResult::Quit(_) | IterateResult::Err(_) => {
-                panic!("PulseAudio: iterate state was not success")
+                error!("PulseAudio: iterate state was not success");
+                break;
             }
+            // operations are cancelled when the context goes away
             IterateResult::Success(_) => {
-                if operation.get_state() == State::Done {
+                if operation.get_state() != State::Running {
                     break;
                 }
             }
@@ -85,6 +120,8 @@ fn create_sink(data: &SinkInfo) -> Option<Sink> {
         .any(|port| port.available != PortAvailable::No)
     {
         Some(Sink {
+            index: data.index,
+            card: data.card,
             name: data
                 .name
                 .as_ref()
@@ -129,11 +166,15 @@ fn create_sink(data: &SinkInfo) -> Option<Sink> {
 
 fn create_source(data: &SourceInfo) -> Option<Source> {
     trace!("create source data: {:?}", data);
+    let is_noise_suppression = data.name.as_deref() == Some(NOISE_SUPPRESSION_SOURCE);
+
     if data.state == SourceState::Running
-        && data.ports.iter().any(|p| p.available != PortAvailable::No)
+        && (is_noise_suppression || data.ports.iter().any(|p| p.available != PortAvailable::No))
         && data.monitor_of_sink.is_none()
     {
         Some(Source {
+            index: data.index,
+            card: data.card,
             name: data
                 .name
                 .as_ref()
@@ -171,11 +212,108 @@ fn create_source(data: &SourceInfo) -> Option<Source> {
                 })
                 .collect::<Vec<_>>(),
         })
+        .map(|mut source| {
+            if is_noise_suppression && source.ports.is_empty() {
+                source.ports = vec![noise_suppression_port()];
+            }
+
+            source
+        })
     } else {
         None
     }
 }
 
+fn create_app_stream(
+    index: u32,
+    name: Option<&str>,
+    proplist: &Proplist,
+    volume: ChannelVolumes,
+    is_mute: bool,
+    device: u32,
+    active: bool,
+) -> AppStream {
+    AppStream {
+        index,
+        name: proplist
+            .get_str(pulse::proplist::properties::APPLICATION_NAME)
+            .or_else(|| name.map(str::to_string))
+            .unwrap_or_default(),
+        icon_name: proplist
+            .get_str(pulse::proplist::properties::APPLICATION_ICON_NAME)
+            .or_else(|| proplist.get_str(pulse::proplist::properties::APPLICATION_PROCESS_BINARY)),
+        volume,
+        is_mute,
+        device,
+        active,
+    }
+}
+
+fn create_sink_input(data: &SinkInputInfo) -> Option<AppStream> {
+    if data.has_volume {
+        Some(create_app_stream(
+            data.index,
+            data.name.as_deref(),
+            &data.proplist,
+            data.volume,
+            data.mute,
+            data.sink,
+            !data.corked,
+        ))
+    } else {
+        None
+    }
+}
+
+fn create_source_output(data: &SourceOutputInfo) -> Option<AppStream> {
+    if data.has_volume
+        && data.proplist.get_str("application.name").as_deref() != Some(SOURCE_METER_NAME)
+    {
+        Some(create_app_stream(
+            data.index,
+            data.name.as_deref(),
+            &data.proplist,
+            data.volume,
+            data.mute,
+            data.source,
+            !data.corked,
+        ))
+    } else {
+        None
+    }
+}
+
+fn create_card(data: &CardInfo) -> Card {
+    Card {
+        index: data.index,
+        description: data
+            .proplist
+            .get_str("device.description")
+            .or_else(|| data.name.as_ref().map(|n| n.to_string()))
+            .unwrap_or_default(),
+        profiles: data
+            .profiles
+            .iter()
+            .filter(|profile| profile.available)
+            .map(|profile| CardProfile {
+                name: profile
+                    .name
+                    .as_ref()
+                    .map_or(String::default(), |n| n.to_string()),
+                description: profile
+                    .description
+                    .as_ref()
+                    .map_or(String::default(), |d| d.to_string()),
+            })
+            .collect(),
+        active_profile: data
+            .active_profile
+            .as_ref()
+            .and_then(|profile| profile.name.as_ref())
+            .map(|n| n.to_string()),
+    }
+}
+
 fn populate_and_send_sinks(
     info: ListResult<&SinkInfo>,
     tx: &tokio::sync::mpsc::UnboundedSender<AudioMessage>,
@@ -214,23 +352,115 @@ fn populate_and_send_sources(
     }
 }
 
+fn populate_and_send_sink_inputs(
+    info: ListResult<&SinkInputInfo>,
+    tx: &tokio::sync::mpsc::UnboundedSender<AudioMessage>,
+    sink_inputs: &mut Vec<AppStream>,
+) {
+    match info {
+        ListResult::Item(data) => {
+            if let Some(sink_input) = create_sink_input(data) {
+                sink_inputs.push(sink_input);
+            }
+        }
+        ListResult::End => {
+            let _ = tx.send(AudioMessage::SinkInputChanges(sink_inputs.clone()));
+            sink_inputs.clear();
+        }
+        ListResult::Error => error!("Error during sink input list population"),
+    }
+}
+
+fn populate_and_send_source_outputs(
+    info: ListResult<&SourceOutputInfo>,
+    tx: &tokio::sync::mpsc::UnboundedSender<AudioMessage>,
+    source_outputs: &mut Vec<AppStream>,
+) {
+    match info {
+        ListResult::Item(data) => {
+            if let Some(source_output) = create_source_output(data) {
+                source_outputs.push(source_output);
+            }
+        }
+        ListResult::End => {
+            let _ = tx.send(AudioMessage::SourceOutputChanges(source_outputs.clone()));
+            source_outputs.clear();
+        }
+        ListResult::Error => error!("Error during source output list population"),
+    }
+}
+
+fn populate_and_send_cards(
+    info: ListResult<&CardInfo>,
+    tx: &tokio::sync::mpsc::UnboundedSender<AudioMessage>,
+    cards: &mut Vec<Card>,
+) {
+    match info {
+        ListResult::Item(data) => cards.push(create_card(data)),
+        ListResult::End => {
+            let _ = tx.send(AudioMessage::CardChanges(cards.clone()));
+            cards.clear();
+        }
+        ListResult::Error => error!("Error during card list population"),
+    }
+}
+
+/// Max volume percentage allowed by the over-amplification config
+pub const MAX_VOLUME: u32 = 150;
+
 pub trait Volume {
     fn get_volume(&self) -> f64;
 
     fn scale_volume(&mut self, max: f64) -> Option<&mut ChannelVolumes>;
+
+    /// Left/right balance between -1 and 1, only stereo volumes have one
+    fn stereo_balance(&self) -> Option<f64>;
+
+    fn set_stereo_balance(&mut self, balance: f64) -> Option<&mut ChannelVolumes>;
 }
 
 impl Volume for ChannelVolumes {
     fn get_volume(&self) -> f64 {
-        self.avg().0 as f64 / libpulse_binding::volume::Volume::NORMAL.0 as f64
+        // same reference channel used by scale_volume
+        self.max().0 as f64 / libpulse_binding::volume::Volume::NORMAL.0 as f64
     }
 
     fn scale_volume(&mut self, max: f64) -> Option<&mut ChannelVolumes> {
-        let max = max.clamp(0.0, 1.0);
+        let max = max.clamp(0.0, MAX_VOLUME as f64 / 100.);
         self.scale(libpulse_binding::volume::Volume(
             (libpulse_binding::volume::Volume::NORMAL.0 as f64 * max) as u32,
         ))
     }
+
+    fn stereo_balance(&self) -> Option<f64> {
+        let [left, right] = self.get() else {
+            return None;
+        };
+        let (left, right) = (left.0 as f64, right.0 as f64);
+
+        Some(if left == right {
+            0.
+        } else if left > right {
+            right / left - 1.
+        } else {
+            1. - left / right
+        })
+    }
+
+    fn set_stereo_balance(&mut self, balance: f64) -> Option<&mut ChannelVolumes> {
+        if self.len() != 2 {
+            return None;
+        }
+
+        // the loudest channel keeps the current volume
+        let balance = balance.clamp(-1., 1.);
+        let max = self.max().0 as f64;
+        let volumes = self.get_mut();
+        volumes[0] = libpulse_binding::volume::Volume((max * (1. - balance.max(0.))) as u32);
+        volumes[1] = libpulse_binding::volume::Volume((max * (1. + balance.min(0.))) as u32);
+
+        Some(self)
+    }
 }
 
 pub trait Sinks {
@@ -265,6 +495,8 @@ impl Sinks for Vec<Sink> {
 
 #[derive(Debug, Clone)]
 pub struct Sink {
+    pub index: u32,
+    pub card: Option<u32>,
     pub name: String,
     pub description: String,
     pub volume: ChannelVolumes,
@@ -274,6 +506,8 @@ pub struct Sink {
 
 #[derive(Debug, Clone)]
 pub struct Source {
+    pub index: u32,
+    pub card: Option<u32>,
     pub name: String,
     pub description: String,
     pub volume: ChannelVolumes,
@@ -281,6 +515,36 @@ pub struct Source {
     pub ports: Vec<Port>,
 }
 
+/// Sound card, its profiles select which sinks and sources are available,
+/// e.g. A2DP playback or HSP/HFP headset mode of a bluetooth headset
+#[derive(Debug, Clone)]
+pub struct Card {
+    pub index: u32,
+    pub description: String,
+    pub profiles: Vec<CardProfile>,
+    pub active_profile: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CardProfile {
+    pub name: String,
+    pub description: String,
+}
+
+/// Application stream, a sink input (playback) or a source output (recording)
+#[derive(Debug, Clone)]
+pub struct AppStream {
+    pub index: u32,
+    pub name: String,
+    pub icon_name: Option<String>,
+    pub volume: ChannelVolumes,
+    pub is_mute: bool,
+    /// Index of the sink or source the stream is connected to
+    pub device: u32,
+    /// False when the stream is paused (corked) or idle
+    pub active: bool,
+}
+
 #[derive(Debug, Clone)]
 pub struct Port {
     pub name: String,
@@ -308,6 +572,62 @@ impl DeviceType {
     }
 }
 
+impl AudioDeviceRule {
+    fn matches(&self, name: &str, description: &str, port: &Port) -> bool {
+        self.pattern
+            .as_ref()
+            .map_or(true, |p| p.is_match(name) || p.is_match(description))
+            && self.port_type.map_or(true, |port_type| {
+                matches!(
+                    (port_type, port.device_type),
+                    (AudioPortType::Headphones, DeviceType::Headphones)
+                        | (AudioPortType::Speaker, DeviceType::Speaker)
+                        | (AudioPortType::Headset, DeviceType::Headset)
+                        | (AudioPortType::Hdmi, DeviceType::Hdmi)
+                )
+            })
+    }
+}
+
+/// Sink or source and port selected by the priority rules, the first matching rule wins.
+/// Devices in the never select list are skipped
+pub fn preferred_device<'a>(
+    devices: impl Iterator<Item = (&'a str, &'a str, &'a [Port])> + Clone,
+    config: &AudioDevicesConfig,
+    rules: &[AudioDeviceRule],
+) -> Option<(String, String)> {
+    let devices = devices.filter(|(name, description, _)| {
+        !config
+            .never_select
+            .iter()
+            .any(|p| p.is_match(name) || p.is_match(description))
+    });
+
+    rules.iter().find_map(|rule| {
+        devices.clone().find_map(|(name, description, ports)| {
+            // keep the active port when it satisfies the rule, e.g. plugged headphones
+            ports
+                .iter()
+                .find(|port| port.active && rule.matches(name, description, port))
+                .or_else(|| {
+                    ports
+                        .iter()
+                        .find(|port| rule.matches(name, description, port))
+                })
+                .map(|port| (name.to_string(), port.name.clone()))
+        })
+    })
+}
+
+/// Volume percentage applied when the device appears
+pub fn default_volume(name: &str, description: &str, config: &AudioDevicesConfig) -> Option<u32> {
+    config
+        .default_volumes
+        .iter()
+        .find(|v| v.pattern.is_match(name) || v.pattern.is_match(description))
+        .map(|v| v.volume)
+}
+
 pub enum AudioCommand {
     SinkMute(String, bool),
     SourceMute(String, bool),
@@ -315,6 +635,15 @@ pub enum AudioCommand {
     SourceVolume(String, ChannelVolumes),
     DefaultSink(String, String),
     DefaultSource(String, String),
+    SinkInputMute(u32, bool),
+    SourceOutputMute(u32, bool),
+    SinkInputVolume(u32, ChannelVolumes),
+    SourceOutputVolume(u32, ChannelVolumes),
+    MoveSinkInput(u32, String),
+    MoveSourceOutput(u32, String),
+    CardProfile(u32, String),
+    /// Loads the filter on top of the given source, or unloads it
+    NoiseSuppression(bool, String),
 }
 
 pub struct AudioCommander {
@@ -324,14 +653,14 @@ pub struct AudioCommander {
 }
 
 impl AudioCommander {
-    pub fn new() -> Self {
-        let (mainloop, context) = init("Ashell-audio-listener");
+    pub fn new() -> Result<Self, Box<dyn Error>> {
+        let (mainloop, context) = init("Ashell-audio-listener")?;
         let introspector = context.borrow().introspect();
-        AudioCommander {
+        Ok(AudioCommander {
             mainloop,
             context,
             introspector,
-        }
+        })
     }
 
     pub fn set_sink_mute(&mut self, name: &str, mute: bool) {
@@ -378,160 +707,756 @@ impl AudioCommander {
         self.wait_for_response(op);
     }
 
+    pub fn set_sink_input_mute(&mut self, index: u32, mute: bool) {
+        let op = self.introspector.set_sink_input_mute(index, mute, None);
+
+        self.wait_for_response(op);
+    }
+
+    pub fn set_source_output_mute(&mut self, index: u32, mute: bool) {
+        let op = self.introspector.set_source_output_mute(index, mute, None);
+
+        self.wait_for_response(op);
+    }
+
+    pub fn set_sink_input_volume(&mut self, index: u32, volume: &ChannelVolumes) {
+        let op = self.introspector.set_sink_input_volume(index, volume, None);
+
+        self.wait_for_response(op);
+    }
+
+    pub fn set_source_output_volume(&mut self, index: u32, volume: &ChannelVolumes) {
+        let op = self
+            .introspector
+            .set_source_output_volume(index, volume, None);
+
+        self.wait_for_response(op);
+    }
+
+    pub fn move_sink_input(&mut self, index: u32, sink: &str) {
+        let op = self.introspector.move_sink_input_by_name(index, sink, None);
+
+        self.wait_for_response(op);
+    }
+
+    pub fn move_source_output(&mut self, index: u32, source: &str) {
+        let op = self
+            .introspector
+            .move_source_output_by_name(index, source, None);
+
+        self.wait_for_response(op);
+    }
+
+    pub fn set_card_profile(&mut self, index: u32, profile: &str) {
+        let op = self
+            .introspector
+            .set_card_profile_by_index(index, profile, None);
+
+        self.wait_for_response(op);
+    }
+
+    pub fn set_noise_suppression(&mut self, enabled: bool, master: &str) {
+        if enabled {
+            let op = self.introspector.load_module(
+                "module-echo-cancel",
+                &format!(
+                    "source_master={} source_name={} sink_name={}_sink aec_method=webrtc \
+                     aec_args=\"noise_suppression=1\" \
+                     source_properties=\"device.description='Noise suppression'\"",
+                    master, NOISE_SUPPRESSION_SOURCE, NOISE_SUPPRESSION_SOURCE
+                ),
+                |index| {
+                    if index == pulse::def::INVALID_INDEX {
+                        error!("Failed to load the noise suppression module");
+                    }
+                },
+            );
+            self.wait_for_response(op);
+
+            let op = self
+                .context
+                .borrow_mut()
+                .set_default_source(NOISE_SUPPRESSION_SOURCE, |_| {});
+            self.wait_for_response(op);
+        } else {
+            let modules = Rc::new(RefCell::new(Vec::new()));
+            let argument = format!("source_name={}", NOISE_SUPPRESSION_SOURCE);
+
+            let op = self.introspector.get_module_info_list({
+                let modules = modules.clone();
+                move |info| {
+                    if let ListResult::Item(info) = info {
+                        if info.name.as_deref() == Some("module-echo-cancel")
+                            && info
+                                .argument
+                                .as_deref()
+                                .is_some_and(|a| a.contains(&argument))
+                        {
+                            modules.borrow_mut().push(info.index);
+                        }
+                    }
+                }
+            });
+            self.wait_for_response(op);
+
+            for index in modules.take() {
+                let op = self.introspector.unload_module(index, |_| {});
+                self.wait_for_response(op);
+            }
+        }
+    }
+
     fn wait_for_response<T: ?Sized>(&self, operation: Operation<T>) {
-        loop {
-            match self.mainloop.borrow_mut().iterate(true) {
-                IterateResult::Quit(_) | IterateResult::Err(_) => {
-                    panic!("PulseAudio: iterate state was not success")
+        wait_for_response(self.mainloop.borrow_mut().deref_mut(), operation);
+    }
+}
+
+fn pulse_commander(mut rx: tokio::sync::mpsc::UnboundedReceiver<AudioCommand>) {
+    let mut commander: Option<AudioCommander> = None;
+
+    while let Some(command) = rx.blocking_recv() {
+        // reconnect lazily, the server could have been restarted since the last command
+        if !commander
+            .as_ref()
+            .is_some_and(|c| is_connected(&c.context.borrow()))
+        {
+            commander = match AudioCommander::new() {
+                Ok(commander) => Some(commander),
+                Err(e) => {
+                    error!("Failed to connect to PulseAudio: {}", e);
+                    None
                 }
-                IterateResult::Success(_) => {
-                    if operation.get_state() == State::Done {
-                        break;
-                    }
+            };
+        }
+
+        if let Some(audio_commander) = commander.as_mut() {
+            match command {
+                AudioCommand::SinkMute(name, mute) => {
+                    audio_commander.set_sink_mute(&name, mute);
+                }
+                AudioCommand::SourceMute(name, mute) => {
+                    audio_commander.set_source_mute(&name, mute);
+                }
+                AudioCommand::SinkVolume(name, volume) => {
+                    audio_commander.set_sink_volume(&name, &volume);
+                }
+                AudioCommand::SourceVolume(name, volume) => {
+                    audio_commander.set_source_volume(&name, &volume);
+                }
+                AudioCommand::DefaultSink(name, port) => {
+                    audio_commander.set_default_sink(&name, &port);
+                }
+                AudioCommand::DefaultSource(name, port) => {
+                    audio_commander.set_default_source(&name, &port);
+                }
+                AudioCommand::SinkInputMute(index, mute) => {
+                    audio_commander.set_sink_input_mute(index, mute);
+                }
+                AudioCommand::SourceOutputMute(index, mute) => {
+                    audio_commander.set_source_output_mute(index, mute);
+                }
+                AudioCommand::SinkInputVolume(index, volume) => {
+                    audio_commander.set_sink_input_volume(index, &volume);
+                }
+                AudioCommand::SourceOutputVolume(index, volume) => {
+                    audio_commander.set_source_output_volume(index, &volume);
+                }
+                AudioCommand::MoveSinkInput(index, sink) => {
+                    audio_commander.move_sink_input(index, &sink);
+                }
+                AudioCommand::MoveSourceOutput(index, source) => {
+                    audio_commander.move_source_output(index, &source);
+                }
+                AudioCommand::CardProfile(index, profile) => {
+                    audio_commander.set_card_profile(index, &profile);
+                }
+                AudioCommand::NoiseSuppression(enabled, master) => {
+                    audio_commander.set_noise_suppression(enabled, &master);
+                }
+            }
+        }
+    }
+}
+
+fn pulse_listener(tx: tokio::sync::mpsc::UnboundedSender<AudioMessage>) {
+    let (mainloop, context) = match init("ashell-audio-listener") {
+        Ok(connection) => connection,
+        Err(e) => {
+            error!("Failed to connect to PulseAudio: {}", e);
+            return;
+        }
+    };
+
+    context.borrow_mut().subscribe(
+        InterestMaskSet::SERVER
+            .union(InterestMaskSet::SINK)
+            .union(InterestMaskSet::SOURCE)
+            .union(InterestMaskSet::SINK_INPUT)
+            .union(InterestMaskSet::SOURCE_OUTPUT)
+            .union(InterestMaskSet::CARD),
+        |res| {
+            if !res {
+                error!("Audio subscription failed!");
+            }
+        },
+    );
+
+    let sinks = Rc::new(RefCell::new(Vec::new()));
+    let sources = Rc::new(RefCell::new(Vec::new()));
+    let sink_inputs = Rc::new(RefCell::new(Vec::new()));
+    let source_outputs = Rc::new(RefCell::new(Vec::new()));
+    let cards = Rc::new(RefCell::new(Vec::new()));
+
+    let introspector = context.borrow().introspect();
+    let get_server = introspector.get_server_info({
+        let tx = tx.clone();
+        move |info| {
+            let _ = tx.send(AudioMessage::DefaultSinkSourceChanged(
+                info.default_sink_name
+                    .as_ref()
+                    .map_or_else(String::default, |s| s.to_string()),
+                info.default_source_name
+                    .as_ref()
+                    .map_or_else(String::default, |s| s.to_string()),
+            ));
+        }
+    });
+    wait_for_response(mainloop.borrow_mut().deref_mut(), get_server);
+    let get_and_send_sinks = introspector.get_sink_info_list({
+        let tx = tx.clone();
+        let sinks = sinks.clone();
+        move |info| populate_and_send_sinks(info, &tx, &mut sinks.borrow_mut())
+    });
+    wait_for_response(mainloop.borrow_mut().deref_mut(), get_and_send_sinks);
+    let get_and_send_source = introspector.get_source_info_list({
+        let tx = tx.clone();
+        let sources = sources.clone();
+        move |info| populate_and_send_sources(info, &tx, &mut sources.borrow_mut())
+    });
+    wait_for_response(mainloop.borrow_mut().deref_mut(), get_and_send_source);
+    let get_and_send_sink_inputs = introspector.get_sink_input_info_list({
+        let tx = tx.clone();
+        let sink_inputs = sink_inputs.clone();
+        move |info| populate_and_send_sink_inputs(info, &tx, &mut sink_inputs.borrow_mut())
+    });
+    wait_for_response(mainloop.borrow_mut().deref_mut(), get_and_send_sink_inputs);
+    let get_and_send_source_outputs = introspector.get_source_output_info_list({
+        let tx = tx.clone();
+        let source_outputs = source_outputs.clone();
+        move |info| populate_and_send_source_outputs(info, &tx, &mut source_outputs.borrow_mut())
+    });
+    wait_for_response(
+        mainloop.borrow_mut().deref_mut(),
+        get_and_send_source_outputs,
+    );
+    let get_and_send_cards = introspector.get_card_info_list({
+        let tx = tx.clone();
+        let cards = cards.clone();
+        move |info| populate_and_send_cards(info, &tx, &mut cards.borrow_mut())
+    });
+    wait_for_response(mainloop.borrow_mut().deref_mut(), get_and_send_cards);
+
+    context.borrow_mut().set_subscribe_callback({
+        let context = context.clone();
+        Some(Box::new(move |_facility, _operation, _idx| {
+            let introspector = context.borrow().introspect();
+            introspector.get_server_info({
+                let tx = tx.clone();
+                move |info| {
+                    let _ = tx.send(AudioMessage::DefaultSinkSourceChanged(
+                        info.default_sink_name
+                            .as_ref()
+                            .map_or_else(String::default, |s| s.to_string()),
+                        info.default_source_name
+                            .as_ref()
+                            .map_or_else(String::default, |s| s.to_string()),
+                    ));
+                }
+            });
+            introspector.get_sink_info_list({
+                let tx = tx.clone();
+                let sinks = sinks.clone();
+
+                move |info| {
+                    populate_and_send_sinks(info, &tx, &mut sinks.borrow_mut());
+                }
+            });
+            introspector.get_source_info_list({
+                let tx = tx.clone();
+                let sources = sources.clone();
+
+                move |info| populate_and_send_sources(info, &tx, &mut sources.borrow_mut())
+            });
+            introspector.get_sink_input_info_list({
+                let tx = tx.clone();
+                let sink_inputs = sink_inputs.clone();
+
+                move |info| populate_and_send_sink_inputs(info, &tx, &mut sink_inputs.borrow_mut())
+            });
+            introspector.get_source_output_info_list({
+                let tx = tx.clone();
+                let source_outputs = source_outputs.clone();
+
+                move |info| {
+                    populate_and_send_source_outputs(info, &tx, &mut source_outputs.borrow_mut())
+                }
+            });
+            introspector.get_card_info_list({
+                let tx = tx.clone();
+                let cards = cards.clone();
+
+                move |info| populate_and_send_cards(info, &tx, &mut cards.borrow_mut())
+            });
+        }))
+    });
+
+    loop {
+        let data = mainloop.borrow_mut().iterate(true);
+        if let IterateResult::Quit(_) | IterateResult::Err(_) = data {
+            error!("PulseAudio mainloop error");
+            break;
+        }
+        if !is_connected(&context.borrow()) {
+            warn!("PulseAudio connection lost");
+            break;
+        }
+    }
+}
+
+/// PipeWire volumes are linear, the PulseAudio ones are cubic
+fn pulse_volume(volumes: &[f32]) -> ChannelVolumes {
+    let volumes = if volumes.is_empty() {
+        &[1.][..]
+    } else {
+        volumes
+    };
+
+    let mut channel_volumes = ChannelVolumes::default();
+    channel_volumes.set_len(volumes.len().min(CHANNELS_MAX as usize) as u8);
+    for (volume, linear) in channel_volumes.get_mut().iter_mut().zip(volumes) {
+        *volume = libpulse_binding::volume::Volume(
+            (linear.cbrt() * libpulse_binding::volume::Volume::NORMAL.0 as f32) as u32,
+        );
+    }
+
+    channel_volumes
+}
+
+fn linear_volumes(volume: &ChannelVolumes) -> Vec<f32> {
+    volume
+        .get()
+        .iter()
+        .map(|v| (v.0 as f32 / libpulse_binding::volume::Volume::NORMAL.0 as f32).powi(3))
+        .collect()
+}
+
+fn pipewire_device_type(port_type: Option<&str>) -> DeviceType {
+    match port_type {
+        Some("headphones") => DeviceType::Headphones,
+        Some("headset") | Some("handsfree") => DeviceType::Headset,
+        Some("hdmi") => DeviceType::Hdmi,
+        _ => DeviceType::Speaker,
+    }
+}
+
+/// Routes of the node device, PipeWire equivalent of the PulseAudio ports
+fn pipewire_ports(state: &PipewireState, node: &Node, direction: Direction) -> Vec<Port> {
+    let (Some(device), Some(profile_device)) = (
+        node.device_id.and_then(|id| state.devices.get(&id)),
+        node.profile_device,
+    ) else {
+        return Vec::new();
+    };
+    let active = device.active_routes.get(&profile_device);
+
+    device
+        .routes
+        .values()
+        .filter(|r| r.direction == direction && r.available && r.devices.contains(&profile_device))
+        .map(|r| Port {
+            name: r.name.clone(),
+            description: r.description.clone(),
+            device_type: pipewire_device_type(r.port_type.as_deref()),
+            active: active == Some(&r.index),
+        })
+        .collect()
+}
+
+fn pipewire_streams(
+    state: &PipewireState,
+    media_class: &str,
+    device_class: &str,
+) -> Vec<AppStream> {
+    state
+        .nodes
+        .values()
+        .filter(|n| {
+            n.media_class == media_class && n.application_name.as_deref() != Some(SOURCE_METER_NAME)
+        })
+        .map(|n| AppStream {
+            index: n.id,
+            name: n
+                .application_name
+                .clone()
+                .unwrap_or_else(|| n.description.clone()),
+            icon_name: n.application_icon.clone(),
+            volume: pulse_volume(&n.volumes),
+            is_mute: n.mute,
+            device: state
+                .linked_nodes(n.id)
+                .find(|id| {
+                    state
+                        .nodes
+                        .get(id)
+                        .is_some_and(|d| d.media_class == device_class)
+                })
+                .unwrap_or_default(),
+            active: n.running,
+        })
+        .collect()
+}
+
+fn send_pipewire_state(
+    state: &PipewireState,
+    tx: &tokio::sync::mpsc::UnboundedSender<AudioMessage>,
+) {
+    let sinks = state
+        .nodes
+        .values()
+        .filter(|n| n.media_class == "Audio/Sink")
+        .filter_map(|n| {
+            let ports = pipewire_ports(state, n, Direction::Output);
+
+            (!ports.is_empty()).then(|| Sink {
+                index: n.id,
+                card: n.device_id,
+                name: n.name.clone(),
+                description: n.description.clone(),
+                volume: pulse_volume(&n.volumes),
+                is_mute: n.mute,
+                ports,
+            })
+        })
+        .collect();
+    let sources = state
+        .nodes
+        .values()
+        .filter(|n| n.media_class == "Audio/Source")
+        .filter_map(|n| {
+            let mut ports = pipewire_ports(state, n, Direction::Input);
+            if ports.is_empty() && n.name == NOISE_SUPPRESSION_SOURCE {
+                ports = vec![noise_suppression_port()];
+            }
+
+            (!ports.is_empty()).then(|| Source {
+                index: n.id,
+                card: n.device_id,
+                name: n.name.clone(),
+                description: n.description.clone(),
+                volume: pulse_volume(&n.volumes),
+                is_mute: n.mute,
+                ports,
+            })
+        })
+        .collect();
+    let cards = state
+        .devices
+        .values()
+        .map(|d| Card {
+            index: d.id,
+            description: d.description.clone(),
+            profiles: d
+                .profiles
+                .values()
+                .filter(|p| p.available)
+                .map(|p| CardProfile {
+                    name: p.name.clone(),
+                    description: p.description.clone(),
+                })
+                .collect(),
+            active_profile: d
+                .active_profile
+                .and_then(|index| d.profiles.get(&index))
+                .map(|p| p.name.clone()),
+        })
+        .collect();
+
+    let _ = tx.send(AudioMessage::DefaultSinkSourceChanged(
+        state.default_sink.clone().unwrap_or_default(),
+        state.default_source.clone().unwrap_or_default(),
+    ));
+    let _ = tx.send(AudioMessage::SinkChanges(sinks));
+    let _ = tx.send(AudioMessage::SourceChanges(sources));
+    let _ = tx.send(AudioMessage::SinkInputChanges(pipewire_streams(
+        state,
+        "Stream/Output/Audio",
+        "Audio/Sink",
+    )));
+    let _ = tx.send(AudioMessage::SourceOutputChanges(pipewire_streams(
+        state,
+        "Stream/Input/Audio",
+        "Audio/Source",
+    )));
+    let _ = tx.send(AudioMessage::CardChanges(cards));
+}
+
+async fn pipewire_listener(
+    pipewire: &'static Pipewire,
+    tx: tokio::sync::mpsc::UnboundedSender<AudioMessage>,
+) {
+    let mut state_rx = pipewire.subscribe();
+
+    loop {
+        let state = state_rx.borrow_and_update().clone();
+        send_pipewire_state(&state, &tx);
+
+        if state_rx.changed().await.is_err() {
+            error!("PipeWire connection closed");
+            break;
+        }
+    }
+}
+
+/// Route command that activates a port of the node device
+fn pipewire_route(state: &PipewireState, name: &str, port: &str) -> Option<PipewireCommand> {
+    let node = state.node_by_name(name)?;
+    let device_id = node.device_id?;
+    let route = state
+        .devices
+        .get(&device_id)?
+        .routes
+        .values()
+        .find(|r| r.name == port)?;
+
+    Some(PipewireCommand::DeviceRoute(
+        device_id,
+        node.profile_device?,
+        route.index,
+    ))
+}
+
+async fn pipewire_commander(
+    pipewire: &'static Pipewire,
+    mut rx: tokio::sync::mpsc::UnboundedReceiver<AudioCommand>,
+) {
+    while let Some(command) = rx.recv().await {
+        let state = pipewire.state();
+        let node_id = |name: &str| state.node_by_name(name).map(|n| n.id);
+
+        let commands = match command {
+            AudioCommand::SinkMute(name, mute) | AudioCommand::SourceMute(name, mute) => {
+                vec![node_id(&name).map(|id| PipewireCommand::NodeMute(id, mute))]
+            }
+            AudioCommand::SinkVolume(name, volume) | AudioCommand::SourceVolume(name, volume) => {
+                vec![node_id(&name)
+                    .map(|id| PipewireCommand::NodeVolume(id, linear_volumes(&volume)))]
+            }
+            AudioCommand::DefaultSink(name, port) => vec![
+                pipewire_route(&state, &name, &port),
+                Some(PipewireCommand::DefaultSink(name)),
+            ],
+            AudioCommand::DefaultSource(name, port) => vec![
+                pipewire_route(&state, &name, &port),
+                Some(PipewireCommand::DefaultSource(name)),
+            ],
+            AudioCommand::SinkInputMute(index, mute)
+            | AudioCommand::SourceOutputMute(index, mute) => {
+                vec![Some(PipewireCommand::NodeMute(index, mute))]
+            }
+            AudioCommand::SinkInputVolume(index, volume)
+            | AudioCommand::SourceOutputVolume(index, volume) => {
+                vec![Some(PipewireCommand::NodeVolume(
+                    index,
+                    linear_volumes(&volume),
+                ))]
+            }
+            AudioCommand::MoveSinkInput(index, name)
+            | AudioCommand::MoveSourceOutput(index, name) => {
+                vec![Some(PipewireCommand::MoveStream(index, name))]
+            }
+            AudioCommand::CardProfile(index, profile) => vec![state
+                .devices
+                .get(&index)
+                .and_then(|d| d.profiles.values().find(|p| p.name == profile))
+                .map(|p| PipewireCommand::DeviceProfile(index, p.index))],
+            // the filter is loaded through the PipeWire pulse server
+            AudioCommand::NoiseSuppression(enabled, master) => {
+                tokio::task::spawn_blocking(move || match AudioCommander::new() {
+                    Ok(mut commander) => commander.set_noise_suppression(enabled, &master),
+                    Err(e) => error!("Failed to connect to the PipeWire pulse server: {}", e),
+                });
+
+                vec![]
+            }
+        };
+
+        for command in commands.into_iter().flatten() {
+            pipewire.send(command);
+        }
+    }
+}
+
+fn pulse_source_meter(source: &str, tx: tokio::sync::mpsc::UnboundedSender<f32>) {
+    let (mainloop, context) = match init(SOURCE_METER_NAME) {
+        Ok(connection) => connection,
+        Err(e) => {
+            error!("Failed to connect the source meter: {}", e);
+            return;
+        }
+    };
+
+    let spec = pulse::sample::Spec {
+        format: pulse::sample::Format::FLOAT32NE,
+        channels: 1,
+        rate: 25,
+    };
+    let Some(stream) = Stream::new(&mut context.borrow_mut(), "Peak detect", &spec, None) else {
+        error!("Failed to create the source meter stream");
+        return;
+    };
+    let stream = Rc::new(RefCell::new(stream));
+
+    // one sample for each read, the server computes the peak of the fragment
+    let attr = BufferAttr {
+        maxlength: u32::MAX,
+        tlength: u32::MAX,
+        prebuf: u32::MAX,
+        minreq: u32::MAX,
+        fragsize: std::mem::size_of::<f32>() as u32,
+    };
+    if let Err(e) = stream.borrow_mut().connect_record(
+        Some(source),
+        Some(&attr),
+        StreamFlagSet::PEAK_DETECT | StreamFlagSet::ADJUST_LATENCY | StreamFlagSet::DONT_MOVE,
+    ) {
+        error!("Failed to connect the source meter stream: {:?}", e);
+        return;
+    }
+
+    let closed = Rc::new(Cell::new(false));
+    stream.borrow_mut().set_read_callback(Some(Box::new({
+        let stream = Rc::downgrade(&stream);
+        let closed = closed.clone();
+        move |_| {
+            let Some(stream) = stream.upgrade() else {
+                return;
+            };
+            let mut stream = stream.borrow_mut();
+
+            let peak = match stream.peek() {
+                Ok(PeekResult::Data(data)) => data
+                    .chunks_exact(4)
+                    .map(|b| f32::from_ne_bytes([b[0], b[1], b[2], b[3]]))
+                    .fold(0., f32::max),
+                Ok(PeekResult::Hole(_)) => {
+                    let _ = stream.discard();
+                    return;
                 }
+                Ok(PeekResult::Empty) | Err(_) => return,
+            };
+            let _ = stream.discard();
+
+            if tx.send(peak.clamp(0., 1.)).is_err() {
+                closed.set(true);
+            }
+        }
+    })));
+
+    while !closed.get() {
+        if let IterateResult::Quit(_) | IterateResult::Err(_) = mainloop.borrow_mut().iterate(true)
+        {
+            error!("PulseAudio: iterate state was not success");
+            break;
+        }
+    }
+
+    let _ = stream.borrow_mut().disconnect();
+}
+
+/// Peak level of the source, the record stream lives as long as the subscription
+pub fn source_meter(source: String) -> Subscription<AudioMessage> {
+    iced::subscription::channel(
+        ("audio-source-meter", source.clone()),
+        100,
+        move |mut output| async move {
+            let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<f32>();
+
+            thread::spawn(move || pulse_source_meter(&source, tx));
+
+            while let Some(peak) = rx.recv().await {
+                let _ = output.send(AudioMessage::SourcePeak(peak)).await;
+            }
+
+            loop {
+                iced::futures::future::pending::<()>().await;
             }
+        },
+    )
+}
+
+/// PipeWire connection if it's the selected backend, otherwise PulseAudio is used
+async fn pipewire_connection(backend: AudioBackend) -> Option<&'static Pipewire> {
+    match backend {
+        AudioBackend::PipeWire => {
+            let connection = crate::utils::pipewire::connect().await;
+            if connection.is_none() {
+                warn!("PipeWire is not available, falling back to PulseAudio");
+            }
+
+            connection
         }
+        AudioBackend::PulseAudio => None,
     }
 }
 
 pub fn subscription(
+    backend: AudioBackend,
     rx: Option<tokio::sync::mpsc::UnboundedReceiver<AudioCommand>>,
 ) -> Subscription<AudioMessage> {
     iced::Subscription::batch(vec![
-        iced::subscription::channel("audio-commander", 100, |_| async move {
+        iced::subscription::channel("audio-commander", 100, move |_| async move {
             let (_internal_tx, mut internal_rx) = tokio::sync::mpsc::unbounded_channel::<()>();
+            let rx = rx.unwrap();
 
-            thread::spawn(move || {
-                let mut rx = rx.unwrap();
-                let mut audio_commander = AudioCommander::new();
-
-                loop {
-                    if let Some(command) = rx.blocking_recv() {
-                        match command {
-                            AudioCommand::SinkMute(name, mute) => {
-                                audio_commander.set_sink_mute(&name, mute);
-                            }
-                            AudioCommand::SourceMute(name, mute) => {
-                                audio_commander.set_source_mute(&name, mute);
-                            }
-                            AudioCommand::SinkVolume(name, volume) => {
-                                audio_commander.set_sink_volume(&name, &volume);
-                            }
-                            AudioCommand::SourceVolume(name, volume) => {
-                                audio_commander.set_source_volume(&name, &volume);
-                            }
-                            AudioCommand::DefaultSink(name, port) => {
-                                audio_commander.set_default_sink(&name, &port);
-                            }
-                            AudioCommand::DefaultSource(name, port) => {
-                                audio_commander.set_default_source(&name, &port);
-                            }
-                        }
-                    }
+            match pipewire_connection(backend).await {
+                Some(pipewire) => {
+                    tokio::spawn(pipewire_commander(pipewire, rx));
                 }
-            });
+                None => {
+                    thread::spawn(move || pulse_commander(rx));
+                }
+            }
 
             loop {
                 let _ = internal_rx.recv().await;
                 trace!("Audio command receive");
             }
         }),
-        iced::subscription::channel("audio-listener", 100, |mut output| async move {
-            let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<AudioMessage>();
-
-            thread::spawn(move || {
-                let (mainloop, context) = init("ashell-audio-listener");
-
-                context.borrow_mut().subscribe(
-                    InterestMaskSet::SERVER
-                        .union(InterestMaskSet::SINK)
-                        .union(InterestMaskSet::SOURCE),
-                    |res| {
-                        if !res {
-                            error!("Audio subscription failed!");
-                        }
-                    },
-                );
+        iced::subscription::channel("audio-listener", 100, move |mut output| async move {
+            let pipewire = pipewire_connection(backend).await;
 
-                let sinks = Rc::new(RefCell::new(Vec::new()));
-                let sources = Rc::new(RefCell::new(Vec::new()));
+            loop {
+                let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<AudioMessage>();
 
-                let introspector = context.borrow().introspect();
-                let get_server = introspector.get_server_info({
-                    let tx = tx.clone();
-                    move |info| {
-                        let _ = tx.send(AudioMessage::DefaultSinkSourceChanged(
-                            info.default_sink_name
-                                .as_ref()
-                                .map_or_else(String::default, |s| s.to_string()),
-                            info.default_source_name
-                                .as_ref()
-                                .map_or_else(String::default, |s| s.to_string()),
-                        ));
+                match pipewire {
+                    Some(pipewire) => {
+                        tokio::spawn(pipewire_listener(pipewire, tx));
                     }
-                });
-                wait_for_response(mainloop.borrow_mut().deref_mut(), get_server);
-                let get_and_send_sinks = introspector.get_sink_info_list({
-                    let tx = tx.clone();
-                    let sinks = sinks.clone();
-                    move |info| populate_and_send_sinks(info, &tx, &mut sinks.borrow_mut())
-                });
-                wait_for_response(mainloop.borrow_mut().deref_mut(), get_and_send_sinks);
-                let get_and_send_source = introspector.get_source_info_list({
-                    let tx = tx.clone();
-                    let sources = sources.clone();
-                    move |info| populate_and_send_sources(info, &tx, &mut sources.borrow_mut())
-                });
-                wait_for_response(mainloop.borrow_mut().deref_mut(), get_and_send_source);
-
-                context.borrow_mut().set_subscribe_callback({
-                    let context = context.clone();
-                    Some(Box::new(move |_facility, _operation, _idx| {
-                        let introspector = context.borrow().introspect();
-                        introspector.get_server_info({
-                            let tx = tx.clone();
-                            move |info| {
-                                let _ = tx.send(AudioMessage::DefaultSinkSourceChanged(
-                                    info.default_sink_name
-                                        .as_ref()
-                                        .map_or_else(String::default, |s| s.to_string()),
-                                    info.default_source_name
-                                        .as_ref()
-                                        .map_or_else(String::default, |s| s.to_string()),
-                                ));
-                            }
-                        });
-                        introspector.get_sink_info_list({
-                            let tx = tx.clone();
-                            let sinks = sinks.clone();
-
-                            move |info| {
-                                populate_and_send_sinks(info, &tx, &mut sinks.borrow_mut());
-                            }
-                        });
-                        introspector.get_source_info_list({
-                            let tx = tx.clone();
-                            let sources = sources.clone();
-
-                            move |info| {
-                                populate_and_send_sources(info, &tx, &mut sources.borrow_mut())
-                            }
-                        });
-                    }))
-                });
-
-                loop {
-                    let data = mainloop.borrow_mut().iterate(true);
-                    if let IterateResult::Quit(_) | IterateResult::Err(_) = data {
-                        error!("PulseAudio mainloop error");
+                    None => {
+                        thread::spawn(move || pulse_listener(tx));
                     }
                 }
-            });
 
-            loop {
-                let data = rx.recv().await.unwrap();
-                let _ = output.send(data).await;
-                trace!("Audio listener receive");
+                while let Some(data) = rx.recv().await {
+                    let _ = output.send(data).await;
+                    trace!("Audio listener receive");
+                }
+
+                warn!(
+                    "Audio listener exited, restarting in {} seconds",
+                    LISTENER_RETRY_INTERVAL.as_secs()
+                );
+                tokio::time::sleep(LISTENER_RETRY_INTERVAL).await;
             }
         }),
     ])
@@ -1,17 +1,53 @@
-use crate::modules::settings::bluetooth::{BluetoothMessage, BluetoothState, Device};
+use crate::modules::settings::bluetooth::{
+    Adapter, AgentRequest, AgentRequestKind, BluetoothMessage, BluetoothState, Device,
+};
 use iced::{
-    futures::{stream::select_all, FutureExt, SinkExt, StreamExt},
+    futures::{
+        channel::mpsc::Sender,
+        stream::{self, select_all},
+        FutureExt, SinkExt, StreamExt,
+    },
     Subscription,
 };
-use std::collections::HashMap;
+use log::{debug, error, warn};
+use std::{collections::HashMap, sync::Arc, time::Duration};
+use tokio::sync::mpsc::UnboundedReceiver;
+use tokio::sync::{oneshot, Mutex};
+use tokio::task::JoinHandle;
 use zbus::{
+    fdo::DBusProxy,
+    interface,
+    message::Type,
+    names::BusName,
     proxy,
-    zvariant::{OwnedObjectPath, OwnedValue},
-    Result,
+    zvariant::{ObjectPath, OwnedObjectPath, OwnedValue},
+    MatchRule, MessageStream, Result,
 };
 
+const BLUEZ_SERVICE: &str = "org.bluez";
+
+const ADAPTER_INTERFACE: &str = "org.bluez.Adapter1";
+
+const DEVICE_INTERFACE: &str = "org.bluez.Device1";
+
+const BATTERY_INTERFACE: &str = "org.bluez.Battery1";
+
 type ManagedObjects = HashMap<OwnedObjectPath, HashMap<String, HashMap<String, OwnedValue>>>;
 
+/// Discovery is stopped after this time, bluez keeps it running until the client stops it
+const DISCOVERY_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How often to check if bluez is back after an error not caused by bluetoothd going away
+const RETRY_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Device properties shown in the devices list, the others, e.g. RSSI, are ignored
+const DEVICE_PROPERTIES: [&str; 4] = ["Name", "Connected", "Paired", "Trusted"];
+
+const AGENT_PATH: &str = "/org/ashell/bluetooth/agent";
+
+/// The user answer to the pending agent request, `None` when the request is rejected
+type AgentReply = Arc<Mutex<Option<oneshot::Sender<Option<String>>>>>;
+
 #[proxy(
     default_service = "org.bluez",
     default_path = "/",
@@ -21,32 +57,62 @@ trait BluezObjectManager {
     fn get_managed_objects(&self) -> Result<ManagedObjects>;
 
     #[zbus(signal)]
-    fn interfaces_added(&self) -> Result<()>;
+    fn interfaces_added(
+        &self,
+        object_path: OwnedObjectPath,
+        interfaces: HashMap<String, HashMap<String, OwnedValue>>,
+    ) -> Result<()>;
 
     #[zbus(signal)]
-    fn interfaces_removed(&self) -> Result<()>;
+    fn interfaces_removed(
+        &self,
+        object_path: OwnedObjectPath,
+        interfaces: Vec<String>,
+    ) -> Result<()>;
 }
 
-#[proxy(
-    default_service = "org.bluez",
-    default_path = "/org/bluez/hci0",
-    interface = "org.bluez.Adapter1"
-)]
+#[proxy(default_service = "org.bluez", interface = "org.bluez.Adapter1")]
 trait Adapter {
+    fn start_discovery(&self) -> Result<()>;
+
+    fn stop_discovery(&self) -> Result<()>;
+
+    fn remove_device(&self, device: &ObjectPath<'_>) -> Result<()>;
+
     #[zbus(property)]
     fn powered(&self) -> Result<bool>;
 
     #[zbus(property)]
     fn set_powered(&self, value: bool) -> Result<()>;
+
+    #[zbus(property)]
+    fn discovering(&self) -> Result<bool>;
 }
 
 #[proxy(default_service = "org.bluez", interface = "org.bluez.Device1")]
 trait Device {
+    fn connect(&self) -> Result<()>;
+
+    fn disconnect(&self) -> Result<()>;
+
+    fn pair(&self) -> Result<()>;
+
     #[zbus(property)]
     fn name(&self) -> Result<String>;
 
     #[zbus(property)]
-    fn connected(&self) -> Result<bool>;
+    fn set_trusted(&self, value: bool) -> Result<()>;
+}
+
+#[proxy(
+    default_service = "org.bluez",
+    default_path = "/org/bluez",
+    interface = "org.bluez.AgentManager1"
+)]
+trait AgentManager {
+    fn register_agent(&self, agent: &ObjectPath<'_>, capability: &str) -> Result<()>;
+
+    fn request_default_agent(&self, agent: &ObjectPath<'_>) -> Result<()>;
 }
 
 #[proxy(default_service = "org.bluez", interface = "org.bluez.Battery1")]
@@ -55,269 +121,674 @@ trait Battery {
     fn percentage(&self) -> Result<u8>;
 }
 
-async fn get_adapter<'a>(bluez: &BluezObjectManagerProxy<'a>) -> Option<OwnedObjectPath> {
-    bluez
+async fn get_adapters<'a>(bluez: &BluezObjectManagerProxy<'a>) -> Result<Vec<Adapter>> {
+    let mut adapters = bluez
         .get_managed_objects()
-        .await
-        .expect("Failed to get bluez managed objects")
+        .await?
         .into_iter()
         .filter_map(|(key, item)| {
-            if item.contains_key("org.bluez.Adapter1") {
-                Some(key)
-            } else {
-                None
-            }
+            item.get(ADAPTER_INTERFACE).map(|adapter| Adapter {
+                name: adapter
+                    .get("Alias")
+                    .and_then(|alias| <&str>::try_from(alias).ok())
+                    .map(|alias| alias.to_owned())
+                    .unwrap_or_else(|| key.rsplit('/').next().unwrap_or_default().to_owned()),
+                path: key.to_string(),
+            })
         })
-        .next()
+        .collect::<Vec<_>>();
+
+    adapters.sort_by(|a, b| a.path.cmp(&b.path));
+
+    Ok(adapters)
 }
 
-async fn get_connected_devices<'a>(
+/// Paired and discovered devices of the adapter, devices without a name are skipped.
+/// The properties are read from the bluez objects, a battery proxy is built only for
+/// the connected devices with a battery to listen to the level changes
+async fn get_devices<'a>(
     conn: &zbus::Connection,
     bluez: &BluezObjectManagerProxy<'a>,
-) -> Vec<(String, Option<(u8, BatteryProxy<'a>)>)> {
-    let bluez = bluez
-        .get_managed_objects()
-        .await
-        .expect("Failed to get bluez managed objects")
-        .into_iter()
-        .filter_map(|(key, item)| {
-            if item.contains_key("org.bluez.Device1") {
-                Some(key)
-            } else {
-                None
+    adapter_path: &str,
+) -> Result<Vec<(Device, Option<BatteryProxy<'a>>)>> {
+    let adapter_prefix = format!("{}/", adapter_path);
+
+    let mut devices = Vec::new();
+    for (device_path, interfaces) in bluez.get_managed_objects().await? {
+        if !device_path.starts_with(&adapter_prefix) {
+            continue;
+        }
+        let Some(device) = interfaces.get(DEVICE_INTERFACE) else {
+            continue;
+        };
+        let Some(name) = device
+            .get("Name")
+            .and_then(|name| <&str>::try_from(name).ok())
+        else {
+            continue;
+        };
+        let flag = |property: &str| {
+            device
+                .get(property)
+                .and_then(|value| bool::try_from(value).ok())
+                .unwrap_or_default()
+        };
+        let connected = flag("Connected");
+
+        let battery = interfaces
+            .get(BATTERY_INTERFACE)
+            .and_then(|battery| battery.get("Percentage"))
+            .and_then(|percentage| u8::try_from(percentage).ok())
+            .filter(|_| connected);
+        let battery_proxy = if battery.is_some() {
+            match BatteryProxy::builder(conn).path(device_path.clone()) {
+                Ok(builder) => builder.build().await.ok(),
+                Err(_) => None,
             }
+        } else {
+            None
+        };
+
+        devices.push((
+            Device {
+                path: device_path.to_string(),
+                name: name.to_owned(),
+                battery,
+                connected,
+                paired: flag("Paired"),
+                trusted: flag("Trusted"),
+            },
+            battery_proxy,
+        ));
+    }
+
+    devices.sort_by(|(a, _), (b, _)| a.name.cmp(&b.name));
+
+    Ok(devices)
+}
+
+/// Property changes of the bluez devices, e.g. a device connected or paired
+async fn device_properties_changed(conn: &zbus::Connection) -> Result<MessageStream> {
+    let rule = MatchRule::builder()
+        .msg_type(Type::Signal)
+        .sender("org.bluez")?
+        .interface("org.freedesktop.DBus.Properties")?
+        .member("PropertiesChanged")?
+        .path_namespace("/org/bluez")?
+        .build();
+
+    MessageStream::for_match_rule(rule, conn, None).await
+}
+
+fn is_device_change(message: &zbus::Message) -> bool {
+    message
+        .body()
+        .deserialize::<(String, HashMap<String, OwnedValue>, Vec<String>)>()
+        .is_ok_and(|(interface, changed, invalidated)| {
+            interface == DEVICE_INTERFACE
+                && changed
+                    .keys()
+                    .chain(invalidated.iter())
+                    .any(|p| DEVICE_PROPERTIES.contains(&p.as_str()))
         })
-        .collect::<Vec<_>>();
+}
 
-    let mut devices = Vec::new();
-    for device_path in bluez {
-        let device = DeviceProxy::builder(conn)
-            .path(device_path.clone())
-            .expect("Failed to set DeviceProxy path")
-            .build()
+#[derive(zbus::DBusError, Debug)]
+#[zbus(prefix = "org.bluez.Error")]
+enum AgentError {
+    #[zbus(error)]
+    ZBus(zbus::Error),
+    Rejected(String),
+    Canceled(String),
+}
+
+/// Pairing agent, every request is forwarded to the UI that answers through the `AgentReply`
+struct Agent {
+    output: Sender<BluetoothMessage>,
+    reply: AgentReply,
+}
+
+impl Agent {
+    async fn notify(
+        &self,
+        conn: &zbus::Connection,
+        device: OwnedObjectPath,
+        kind: AgentRequestKind,
+    ) {
+        let name = match DeviceProxy::builder(conn).path(device.clone()) {
+            Ok(builder) => match builder.build().await {
+                Ok(proxy) => proxy.name().await.ok(),
+                Err(_) => None,
+            },
+            Err(_) => None,
+        };
+
+        let _ = self
+            .output
+            .clone()
+            .send(BluetoothMessage::AgentRequest(AgentRequest {
+                path: device.to_string(),
+                device: name.unwrap_or_else(|| device.to_string()),
+                kind,
+            }))
+            .await;
+    }
+
+    async fn request(
+        &self,
+        conn: &zbus::Connection,
+        device: OwnedObjectPath,
+        kind: AgentRequestKind,
+    ) -> std::result::Result<String, AgentError> {
+        let (tx, rx) = oneshot::channel();
+        // bluez sends a single request at a time, a new one replaces the previous
+        self.reply.lock().await.replace(tx);
+
+        self.notify(conn, device, kind).await;
+
+        match rx.await {
+            Ok(Some(value)) => Ok(value),
+            Ok(None) => Err(AgentError::Rejected("Rejected by the user".to_owned())),
+            Err(_) => Err(AgentError::Canceled("Request canceled".to_owned())),
+        }
+    }
+}
+
+#[interface(name = "org.bluez.Agent1")]
+impl Agent {
+    async fn release(&self) {
+        debug!("Bluetooth agent released");
+    }
+
+    async fn request_pin_code(
+        &self,
+        #[zbus(connection)] conn: &zbus::Connection,
+        device: OwnedObjectPath,
+    ) -> std::result::Result<String, AgentError> {
+        self.request(conn, device, AgentRequestKind::PinCode).await
+    }
+
+    async fn display_pin_code(
+        &self,
+        #[zbus(connection)] conn: &zbus::Connection,
+        device: OwnedObjectPath,
+        pincode: String,
+    ) {
+        self.notify(conn, device, AgentRequestKind::DisplayPinCode(pincode))
+            .await;
+    }
+
+    async fn request_passkey(
+        &self,
+        #[zbus(connection)] conn: &zbus::Connection,
+        device: OwnedObjectPath,
+    ) -> std::result::Result<u32, AgentError> {
+        self.request(conn, device, AgentRequestKind::Passkey)
+            .await?
+            .trim()
+            .parse()
+            .map_err(|_| AgentError::Rejected("Invalid passkey".to_owned()))
+    }
+
+    async fn display_passkey(
+        &self,
+        #[zbus(connection)] conn: &zbus::Connection,
+        device: OwnedObjectPath,
+        passkey: u32,
+        _entered: u16,
+    ) {
+        self.notify(conn, device, AgentRequestKind::DisplayPasskey(passkey))
+            .await;
+    }
+
+    async fn request_confirmation(
+        &self,
+        #[zbus(connection)] conn: &zbus::Connection,
+        device: OwnedObjectPath,
+        passkey: u32,
+    ) -> std::result::Result<(), AgentError> {
+        self.request(conn, device, AgentRequestKind::Confirmation(passkey))
+            .await
+            .map(|_| ())
+    }
+
+    async fn request_authorization(
+        &self,
+        #[zbus(connection)] conn: &zbus::Connection,
+        device: OwnedObjectPath,
+    ) -> std::result::Result<(), AgentError> {
+        self.request(conn, device, AgentRequestKind::Authorization)
+            .await
+            .map(|_| ())
+    }
+
+    async fn authorize_service(
+        &self,
+        #[zbus(connection)] conn: &zbus::Connection,
+        device: OwnedObjectPath,
+        uuid: String,
+    ) -> std::result::Result<(), AgentError> {
+        self.request(conn, device, AgentRequestKind::AuthorizeService(uuid))
             .await
-            .expect("Failed to build DeviceProxy");
+            .map(|_| ())
+    }
 
-        if let Ok(name) = device.name().await {
-            let connected = device.connected().await;
+    async fn cancel(&self) {
+        self.reply.lock().await.take();
+        let _ = self
+            .output
+            .clone()
+            .send(BluetoothMessage::AgentCancelled)
+            .await;
+    }
+}
 
-            if connected == Ok(true) {
-                let battery = if let Ok(battery) = BatteryProxy::builder(conn).path(device_path) {
-                    let battery = battery.build().await;
+async fn register_agent(conn: &zbus::Connection, agent: Agent) -> Result<()> {
+    conn.object_server().at(AGENT_PATH, agent).await?;
 
-                    if let Ok(battery) = battery {
-                        let battery_value = battery.percentage().await;
-                        if let Ok(battery_value) = battery_value {
-                            Some((battery_value, battery))
-                        } else {
-                            None
-                        }
-                    } else {
-                        None
-                    }
+    let path = ObjectPath::try_from(AGENT_PATH)?;
+    let manager = AgentManagerProxy::new(conn).await?;
+    manager.register_agent(&path, "KeyboardDisplay").await?;
+
+    // needed to answer the pairing requests started from the other device
+    if let Err(e) = manager.request_default_agent(&path).await {
+        debug!("Failed to become the default bluetooth agent: {}", e);
+    }
+
+    Ok(())
+}
+
+pub enum BluetoothCommand {
+    TogglePower,
+    StartDiscovery,
+    Pair(String),
+    Connect(String),
+    Disconnect(String),
+    Trust(String, bool),
+    Remove(String),
+    AgentReply(Option<String>),
+    SelectAdapter(String),
+}
+
+async fn handle_command(
+    command: BluetoothCommand,
+    conn: &zbus::Connection,
+    adapter: Option<&AdapterProxy<'static>>,
+    agent_reply: &AgentReply,
+    discovery_timer: &mut Option<JoinHandle<()>>,
+    output: &mut Sender<BluetoothMessage>,
+) {
+    if let BluetoothCommand::AgentReply(value) = command {
+        if let Some(reply) = agent_reply.lock().await.take() {
+            let _ = reply.send(value);
+        }
+        return;
+    }
+
+    let Some(adapter) = adapter else {
+        debug!("No bluetooth adapter available, command ignored");
+        return;
+    };
+
+    match command {
+        BluetoothCommand::AgentReply(_) | BluetoothCommand::SelectAdapter(_) => {}
+        BluetoothCommand::TogglePower => {
+            let current_state = match adapter.powered().await {
+                Ok(state) => state,
+                Err(e) => {
+                    warn!("Failed to get adapter powered state: {}", e);
+                    return;
+                }
+            };
+            if let Err(e) = adapter.set_powered(!current_state).await {
+                warn!("Failed to set adapter powered state: {}", e);
+                return;
+            }
+            let _ = output
+                .send(BluetoothMessage::Status(if !current_state {
+                    BluetoothState::Inactive
                 } else {
-                    None
+                    BluetoothState::Active
+                }))
+                .await;
+        }
+        BluetoothCommand::StartDiscovery => {
+            if let Err(e) = adapter.start_discovery().await {
+                warn!("Failed to start bluetooth discovery: {}", e);
+                return;
+            }
+
+            // a new scan restarts the timeout of the running one
+            if let Some(timer) = discovery_timer.take() {
+                timer.abort();
+            }
+            let adapter = adapter.clone();
+            *discovery_timer = Some(tokio::spawn(async move {
+                tokio::time::sleep(DISCOVERY_TIMEOUT).await;
+                let _ = adapter.stop_discovery().await;
+            }));
+        }
+        BluetoothCommand::Remove(path) => {
+            let result = match ObjectPath::try_from(path.as_str()) {
+                Ok(device_path) => adapter.remove_device(&device_path).await,
+                Err(e) => Err(e.into()),
+            };
+
+            let _ = output
+                .send(BluetoothMessage::ActionFinished(
+                    path,
+                    result.err().map(|e| e.to_string()),
+                ))
+                .await;
+        }
+        BluetoothCommand::Pair(ref path)
+        | BluetoothCommand::Connect(ref path)
+        | BluetoothCommand::Disconnect(ref path)
+        | BluetoothCommand::Trust(ref path, _) => {
+            let path = path.clone();
+            let device = match DeviceProxy::builder(conn).path(path.clone()) {
+                Ok(builder) => builder.build().await,
+                Err(e) => Err(e),
+            };
+            let device = match device {
+                Ok(device) => device,
+                Err(e) => {
+                    let _ = output
+                        .send(BluetoothMessage::ActionFinished(path, Some(e.to_string())))
+                        .await;
+                    return;
+                }
+            };
+
+            // pairing and connecting could take a while, the listener must not wait for them
+            let mut output = output.clone();
+            tokio::spawn(async move {
+                let result = match command {
+                    BluetoothCommand::Pair(_) => match device.pair().await {
+                        Ok(_) => device.connect().await,
+                        Err(e) => Err(e),
+                    },
+                    BluetoothCommand::Connect(_) => device.connect().await,
+                    BluetoothCommand::Disconnect(_) => device.disconnect().await,
+                    BluetoothCommand::Trust(_, trusted) => {
+                        device.set_trusted(trusted).await.map_err(zbus::Error::from)
+                    }
+                    _ => Ok(()),
                 };
+                debug!("Bluetooth device {} action result: {:?}", path, result);
+
+                let _ = output
+                    .send(BluetoothMessage::ActionFinished(
+                        path,
+                        result.err().map(|e| e.to_string()),
+                    ))
+                    .await;
+            });
+        }
+    }
+}
+
+/// Listens to the bluez changes until bluetoothd goes away or a bluez call fails
+async fn listen(
+    conn: &zbus::Connection,
+    rx: &mut UnboundedReceiver<BluetoothCommand>,
+    output: &mut Sender<BluetoothMessage>,
+    agent_reply: &AgentReply,
+    selected_adapter: &mut Option<String>,
+    owner_changed: &mut (impl iced::futures::Stream + Unpin),
+) -> Result<()> {
+    let bluez = BluezObjectManagerProxy::new(conn).await?;
+
+    // bluez forgets the agent when bluetoothd restarts
+    if let Err(e) = register_agent(
+        conn,
+        Agent {
+            output: output.clone(),
+            reply: agent_reply.clone(),
+        },
+    )
+    .await
+    {
+        warn!("Failed to register the bluetooth agent: {}", e);
+    }
+
+    let mut added_signal = bluez.receive_interfaces_added().await?;
+    let mut removed_signal = bluez.receive_interfaces_removed().await?;
+    let mut properties_signal = device_properties_changed(conn).await?;
+    let mut discovery_timer = None;
+
+    'adapters: loop {
+        let adapters = get_adapters(&bluez).await?;
+        let adapter_path = selected_adapter
+            .as_ref()
+            .filter(|path| adapters.iter().any(|a| a.path == **path))
+            .or_else(|| adapters.first().map(|a| &a.path))
+            .cloned();
+        selected_adapter.clone_from(&adapter_path);
+
+        let _ = output
+            .send(BluetoothMessage::Adapters(adapters, adapter_path.clone()))
+            .await;
+
+        let adapter = match &adapter_path {
+            Some(path) => Some(
+                AdapterProxy::builder(conn)
+                    .path(path.clone())?
+                    .build()
+                    .await?,
+            ),
+            None => None,
+        };
+
+        let state = match &adapter {
+            Some(adapter) => {
+                if adapter.powered().await? {
+                    BluetoothState::Active
+                } else {
+                    BluetoothState::Inactive
+                }
+            }
+            None => BluetoothState::Unavailable,
+        };
+        let _ = output.send(BluetoothMessage::Status(state)).await;
 
-                devices.push((name, battery));
+        let (mut powered_signal, mut discovering_signal) = match &adapter {
+            Some(adapter) => (
+                adapter.receive_powered_changed().await.boxed(),
+                adapter.receive_discovering_changed().await.boxed(),
+            ),
+            None => (stream::pending().boxed(), stream::pending().boxed()),
+        };
+
+        'devices: loop {
+            let devices = match &adapter_path {
+                Some(path) => get_devices(conn, &bluez, path).await?,
+                None => Vec::new(),
+            };
+            let mut battery_signals = Vec::new();
+            for (i, b) in devices
+                .iter()
+                .enumerate()
+                .filter_map(|(i, (_, b))| b.as_ref().map(|b| (i, b)))
+            {
+                battery_signals.push(b.receive_percentage_changed().await.map(move |v| (i, v)))
+            }
+
+            let _ = output
+                .send(BluetoothMessage::DeviceList(
+                    devices.into_iter().map(|(d, _)| d).collect::<Vec<_>>(),
+                ))
+                .await;
+
+            let mut battery_signals = if battery_signals.is_empty() {
+                stream::pending().boxed()
+            } else {
+                select_all(battery_signals).boxed()
+            };
+
+            // wait for a change that requires the devices list to be refreshed
+            loop {
+                iced::futures::select! {
+                    v = rx.recv().fuse() => {
+                        match v {
+                            Some(BluetoothCommand::SelectAdapter(path)) => {
+                                // the timer would stop the discovery of the new adapter
+                                if let (Some(timer), Some(adapter)) = (discovery_timer.take(), &adapter) {
+                                    timer.abort();
+                                    let _ = adapter.stop_discovery().await;
+                                }
+                                *selected_adapter = Some(path);
+                                continue 'adapters;
+                            }
+                            Some(v) => {
+                                handle_command(
+                                    v,
+                                    conn,
+                                    adapter.as_ref(),
+                                    agent_reply,
+                                    &mut discovery_timer,
+                                    output,
+                                )
+                                .await;
+                            }
+                            None => {}
+                        }
+                    },
+                    v = powered_signal.next().fuse() => {
+                        if let Some(v) = v {
+                            if let Ok(value) = v.get().await {
+                                let _ = output.send(
+                                    BluetoothMessage::Status(if value {
+                                        BluetoothState::Active
+                                    } else {
+                                        BluetoothState::Inactive
+                                    })
+                                ).await;
+                            }
+                        }
+                    },
+                    v = discovering_signal.next().fuse() => {
+                        if let Some(v) = v {
+                            if let Ok(value) = v.get().await {
+                                let _ = output.send(
+                                    BluetoothMessage::Discovering(value)
+                                ).await;
+                            }
+                        }
+                    },
+                    v = added_signal.next().fuse() => {
+                        if v.is_some_and(|v| v.args().is_ok_and(|args| args.interfaces().contains_key(ADAPTER_INTERFACE))) {
+                            continue 'adapters;
+                        }
+                        continue 'devices;
+                    },
+                    v = removed_signal.next().fuse() => {
+                        if v.is_some_and(|v| v.args().is_ok_and(|args| args.interfaces().iter().any(|i| i == ADAPTER_INTERFACE))) {
+                            continue 'adapters;
+                        }
+                        continue 'devices;
+                    },
+                    v = properties_signal.next().fuse() => {
+                        if v.is_some_and(|m| m.is_ok_and(|m| is_device_change(&m))) {
+                            continue 'devices;
+                        }
+                    },
+                    _ = battery_signals.next().fuse() => {
+                        continue 'devices;
+                    },
+                    _ = owner_changed.next().fuse() => {
+                        return Ok(());
+                    }
+                }
             }
         }
     }
+}
 
-    devices
+async fn bluez_available(dbus: &DBusProxy<'_>) -> bool {
+    match BusName::try_from(BLUEZ_SERVICE) {
+        Ok(name) => dbus.name_has_owner(name).await.unwrap_or_default(),
+        Err(_) => false,
+    }
 }
 
-pub enum BluetoothCommand {
-    TogglePower,
+async fn send_unavailable(output: &mut Sender<BluetoothMessage>, agent_reply: &AgentReply) {
+    agent_reply.lock().await.take();
+
+    let _ = output.send(BluetoothMessage::AgentCancelled).await;
+    let _ = output.send(BluetoothMessage::Discovering(false)).await;
+    let _ = output
+        .send(BluetoothMessage::Adapters(Vec::new(), None))
+        .await;
+    let _ = output.send(BluetoothMessage::DeviceList(Vec::new())).await;
+    let _ = output
+        .send(BluetoothMessage::Status(BluetoothState::Unavailable))
+        .await;
 }
 
 pub fn subscription(
-    rx: Option<tokio::sync::mpsc::UnboundedReceiver<BluetoothCommand>>,
+    rx: Option<UnboundedReceiver<BluetoothCommand>>,
 ) -> Subscription<BluetoothMessage> {
     iced::subscription::channel(
         "bluez-dbus-connection-listener",
         100,
         |mut output| async move {
             let mut rx = rx.expect("Failed to get commander receiver");
-            let conn = zbus::Connection::system()
-                .await
-                .expect("Failed to connect to system bus");
-
-            let bluez = BluezObjectManagerProxy::new(&conn)
-                .await
-                .expect("Failed to create bluez obj manager proxy");
+            let agent_reply = AgentReply::default();
+            let mut selected_adapter = None;
 
-            let adapter_path = get_adapter(&bluez).await;
+            let bus = async {
+                let conn = zbus::Connection::system().await?;
+                let dbus = DBusProxy::new(&conn).await?;
+                let owner_changed = dbus
+                    .receive_name_owner_changed_with_args(&[(0, BLUEZ_SERVICE)])
+                    .await?;
 
-            if let Some(adapter_path) = adapter_path {
-                let adapter = AdapterProxy::builder(&conn)
-                    .path(adapter_path)
-                    .expect("Failed to set adapter proxy path")
-                    .build()
-                    .await
-                    .expect("Failed to create adapter proxy");
+                Ok::<_, zbus::Error>((conn, dbus, owner_changed))
+            };
 
-                let _ = output
-                    .send(BluetoothMessage::Status(
-                        if adapter
-                            .powered()
-                            .await
-                            .expect("Failed to get adapter powered state")
+            match bus.await {
+                Ok((conn, dbus, mut owner_changed)) => loop {
+                    if bluez_available(&dbus).await {
+                        if let Err(e) = listen(
+                            &conn,
+                            &mut rx,
+                            &mut output,
+                            &agent_reply,
+                            &mut selected_adapter,
+                            &mut owner_changed,
+                        )
+                        .await
                         {
-                            BluetoothState::Active
-                        } else {
-                            BluetoothState::Inactive
-                        },
-                    ))
-                    .await;
-
-                let mut powered_signal = adapter.receive_powered_changed().await;
-                let mut added_signal = bluez
-                    .receive_interfaces_added()
-                    .await
-                    .expect("Failed to receive bluez interfaces added signal");
-                let mut removed_signal = bluez
-                    .receive_interfaces_removed()
-                    .await
-                    .expect("Failed to receive bluez interfaces removed signal");
-
-                loop {
-                    let devices = get_connected_devices(&conn, &bluez).await;
-                    let mut battery_signals = Vec::new();
-                    for (i, b) in devices.iter().enumerate().filter_map(|(i, (_, b))| {
-                        if let Some((_, b)) = b {
-                            Some((i, b))
-                        } else {
-                            None
+                            warn!("Bluetooth listener error: {}", e);
                         }
-                    }) {
-                        battery_signals
-                            .push(b.receive_percentage_changed().await.map(move |v| (i, v)))
                     }
 
-                    let _ = output
-                        .send(BluetoothMessage::DeviceList(
-                            devices
-                                .into_iter()
-                                .map(|(n, b)| Device {
-                                    name: n.to_owned(),
-                                    battery: b.as_ref().map(|(v, _)| *v),
-                                })
-                                .collect::<Vec<_>>(),
-                        ))
-                        .await;
+                    send_unavailable(&mut output, &agent_reply).await;
 
-                    if battery_signals.is_empty() {
+                    // wait for bluetoothd to come back, the commands are dropped meanwhile
+                    loop {
                         iced::futures::select! {
-                            v = rx.recv().fuse() => {
-                                if let Some(v) = v {
-                                    match v {
-                                        BluetoothCommand::TogglePower => {
-                                            let current_state = adapter
-                                                .powered()
-                                                .await
-                                                .expect(
-                                                    "Failed to get adapter powered state"
-                                                );
-                                            adapter
-                                                .set_powered(!current_state)
-                                                .await
-                                                .expect(
-                                                    "Failed to set adapter powered state"
-                                                );
-                                            let _ = output
-                                                .send(
-                                                    BluetoothMessage::Status(
-                                                        if !current_state {
-                                                            BluetoothState::Inactive
-                                                        } else {
-                                                            BluetoothState::Active
-                                                        }
-                                                    )
-                                                )
-                                                .await;
-                                        },
-                                    }
-                                }
-                            },
-                            v = powered_signal.next().fuse() => {
-                                if let Some(v) = v {
-                                    if let Ok(value) = v.get().await {
-                                        let _ = output.send(
-                                            BluetoothMessage::Status(if value {
-                                                BluetoothState::Active
-                                            } else {
-                                                BluetoothState::Inactive
-                                            })
-                                        ).await;
-                                    }
-                                }
-                            },
-                            _ = added_signal.next().fuse() => {
-                            },
-                            _ = removed_signal.next().fuse() => {
-                            },
+                            _ = owner_changed.next().fuse() => {},
+                            _ = tokio::time::sleep(RETRY_INTERVAL).fuse() => {},
+                            _ = rx.recv().fuse() => {
+                                continue;
+                            }
                         }
-                    } else {
-                        let mut battery_signals = select_all(battery_signals);
 
-                        iced::futures::select! {
-                            v = rx.recv().fuse() => {
-                                if let Some(v) = v {
-                                    match v {
-                                        BluetoothCommand::TogglePower => {
-                                            let current_state = adapter
-                                                .powered()
-                                                .await
-                                                .expect(
-                                                    "Failed to get adapter powered state"
-                                                );
-                                            adapter
-                                                .set_powered(!current_state)
-                                                .await
-                                                .expect(
-                                                    "Failed to set adapter powered state"
-                                                );
-                                            let _ = output
-                                                .send(
-                                                    BluetoothMessage::Status(
-                                                        if !current_state {
-                                                            BluetoothState::Inactive
-                                                        } else {
-                                                            BluetoothState::Active
-                                                        }
-                                                    )
-                                                )
-                                                .await;
-                                        },
-                                    }
-                                }
-                            },
-                            v = powered_signal.next().fuse() => {
-                                if let Some(v) = v {
-                                    if let Ok(value) = v.get().await {
-                                        let _ = output.send(
-                                            BluetoothMessage::Status(if value {
-                                                BluetoothState::Active
-                                            } else {
-                                                BluetoothState::Inactive
-                                            })
-                                        ).await;
-                                    }
-                                }
-                            },
-                            _ = added_signal.next().fuse() => {
-                            },
-                            _ = removed_signal.next().fuse() => {
-                            },
-                            _ = battery_signals.next().fuse() => {
-                            }
+                        if bluez_available(&dbus).await {
+                            break;
                         }
-                    };
-                }
-            } else {
-                loop {
-                    rx.recv().await;
+                    }
+                },
+                Err(e) => {
+                    error!("Failed to connect to the system bus: {}", e);
+
+                    send_unavailable(&mut output, &agent_reply).await;
+
+                    loop {
+                        rx.recv().await;
+                    }
                 }
             }
         },
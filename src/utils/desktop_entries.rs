@@ -0,0 +1,226 @@
+use log::debug;
+use std::{
+    collections::{HashMap, HashSet},
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+#[derive(Debug, Clone)]
+pub struct DesktopAction {
+    pub name: String,
+    pub exec: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct DesktopEntry {
+    /// Desktop file id, e.g. `org.gnome.Nautilus.desktop`
+    pub id: String,
+    pub name: String,
+    pub generic_name: Option<String>,
+    pub keywords: Vec<String>,
+    pub exec: String,
+    pub icon: Option<String>,
+    pub terminal: bool,
+    pub startup_wm_class: Option<String>,
+    pub actions: Vec<DesktopAction>,
+}
+
+impl DesktopEntry {
+    /// Id without the `.desktop` extension, it usually matches the window class
+    pub fn app_id(&self) -> &str {
+        self.id.strip_suffix(".desktop").unwrap_or(&self.id)
+    }
+
+    /// Tells if a window class belongs to this application, the class can be the
+    /// StartupWMClass, the app id, its last reverse DNS segment or the executable name
+    pub fn matches_class(&self, class: &str) -> bool {
+        let class = class.to_lowercase();
+        let app_id = self.app_id().to_lowercase();
+
+        self.startup_wm_class
+            .as_ref()
+            .is_some_and(|c| c.to_lowercase() == class)
+            || app_id == class
+            || app_id.rsplit('.').next() == Some(class.as_str())
+            || self
+                .exec_binary()
+                .is_some_and(|b| b.to_lowercase() == class)
+    }
+
+    /// File name of the executable, without the env assignments and the path
+    fn exec_binary(&self) -> Option<&str> {
+        self.exec
+            .split_whitespace()
+            .find(|arg| *arg != "env" && !arg.contains('='))
+            .and_then(|arg| arg.rsplit('/').next())
+    }
+
+    /// Shell command to launch the application or one of its actions
+    pub fn command(&self, action: Option<usize>, terminal_cmd: &str) -> Option<String> {
+        let exec = match action {
+            Some(index) => &self.actions.get(index)?.exec,
+            None => &self.exec,
+        };
+        let exec = strip_field_codes(exec);
+
+        Some(if self.terminal {
+            format!("{} {}", terminal_cmd, exec)
+        } else {
+            exec
+        })
+    }
+}
+
+/// Removes the `%f`, `%U`, ... placeholders from an Exec value, we never pass files or urls
+fn strip_field_codes(exec: &str) -> String {
+    let mut command = String::with_capacity(exec.len());
+    let mut chars = exec.chars();
+
+    while let Some(c) = chars.next() {
+        if c == '%' {
+            if let Some('%') = chars.next() {
+                command.push('%');
+            }
+        } else {
+            command.push(c);
+        }
+    }
+
+    command.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Directories searched for desktop entries, ordered by precedence
+fn applications_dirs() -> Vec<PathBuf> {
+    let home = env::var("HOME").unwrap_or_default();
+    let data_home = env::var("XDG_DATA_HOME")
+        .ok()
+        .filter(|d| !d.is_empty())
+        .unwrap_or_else(|| format!("{}/.local/share", home));
+    let data_dirs = env::var("XDG_DATA_DIRS")
+        .ok()
+        .filter(|d| !d.is_empty())
+        .unwrap_or_else(|| "/usr/local/share:/usr/share".to_string());
+
+    std::iter::once(data_home.as_str())
+        .chain(data_dirs.split(':'))
+        .filter(|dir| !dir.is_empty())
+        .map(|dir| Path::new(dir).join("applications"))
+        .collect()
+}
+
+fn collect_desktop_files(base: &Path, dir: &Path, files: &mut Vec<(String, PathBuf)>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_desktop_files(base, &path, files);
+        } else if path.extension().is_some_and(|ext| ext == "desktop") {
+            // the desktop file id uses `-` in place of the subdirectory separators
+            if let Ok(relative) = path.strip_prefix(base) {
+                let id = relative.to_string_lossy().replace('/', "-");
+                files.push((id, path));
+            }
+        }
+    }
+}
+
+fn parse_desktop_entry(id: String, content: &str) -> Option<DesktopEntry> {
+    let mut groups: HashMap<String, HashMap<String, String>> = HashMap::new();
+    let mut current: Option<String> = None;
+
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        if let Some(group) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            current = Some(group.to_string());
+        } else if let (Some(group), Some((key, value))) = (&current, line.split_once('=')) {
+            // localized keys, e.g. Name[it], are ignored
+            groups
+                .entry(group.clone())
+                .or_default()
+                .entry(key.trim().to_string())
+                .or_insert_with(|| value.trim().to_string());
+        }
+    }
+
+    let main = groups.get("Desktop Entry")?;
+    let is_true = |key: &str| main.get(key).is_some_and(|v| v == "true");
+
+    if main.get("Type").map(String::as_str) != Some("Application")
+        || is_true("NoDisplay")
+        || is_true("Hidden")
+    {
+        return None;
+    }
+
+    let list = |value: Option<&String>| {
+        value
+            .map(|v| {
+                v.split(';')
+                    .filter(|s| !s.is_empty())
+                    .map(str::to_string)
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default()
+    };
+
+    let actions = list(main.get("Actions"))
+        .into_iter()
+        .filter_map(|action| {
+            let group = groups.get(&format!("Desktop Action {}", action))?;
+
+            Some(DesktopAction {
+                name: group.get("Name")?.clone(),
+                exec: group.get("Exec")?.clone(),
+            })
+        })
+        .collect();
+
+    Some(DesktopEntry {
+        id,
+        name: main.get("Name")?.clone(),
+        generic_name: main.get("GenericName").cloned(),
+        keywords: list(main.get("Keywords")),
+        exec: main.get("Exec")?.clone(),
+        icon: main.get("Icon").cloned(),
+        terminal: is_true("Terminal"),
+        startup_wm_class: main.get("StartupWMClass").cloned(),
+        actions,
+    })
+}
+
+/// Reads all the visible applications, an id found in more directories
+/// is taken from the one with the highest precedence
+pub fn get_desktop_entries() -> Vec<DesktopEntry> {
+    let mut files = Vec::new();
+    for dir in applications_dirs() {
+        collect_desktop_files(&dir, &dir, &mut files);
+    }
+
+    let mut seen = HashSet::new();
+    let entries = files
+        .into_iter()
+        .filter(|(id, _)| seen.insert(id.clone()))
+        .filter_map(|(id, path)| {
+            let content = fs::read_to_string(&path).ok()?;
+            parse_desktop_entry(id, &content)
+        })
+        .collect::<Vec<_>>();
+
+    debug!("Found {} desktop entries", entries.len());
+
+    entries
+}
+
+/// Same as [`get_desktop_entries`], the directories are scanned on the blocking thread pool
+pub async fn load_desktop_entries() -> Vec<DesktopEntry> {
+    tokio::task::spawn_blocking(get_desktop_entries)
+        .await
+        .unwrap_or_default()
+}
@@ -3,12 +3,16 @@ use std::{cell::RefCell, ops::Deref, time::Duration};
 pub mod audio;
 pub mod battery;
 pub mod brightness;
+pub mod desktop_entries;
 pub mod launcher;
 pub mod net;
+pub mod notifications;
+pub mod pipewire;
 pub mod bluetooth;
 pub mod powerprofiles;
 pub mod privacy;
 pub mod idle_inhibitor;
+pub mod toplevels;
 
 pub struct Commander<T> {
     sender: tokio::sync::mpsc::UnboundedSender<T>,
@@ -53,3 +57,24 @@ pub fn format_duration(duration: &Duration) -> String {
         format!("{:>2}m", m)
     }
 }
+
+pub fn format_bytes(bytes: f64) -> String {
+    const UNITS: [&str; 5] = ["B", "K", "M", "G", "T"];
+
+    let mut value = bytes.max(0.);
+    let mut unit = 0;
+    while value >= 1024. && unit < UNITS.len() - 1 {
+        value /= 1024.;
+        unit += 1;
+    }
+
+    if value < 10. && unit > 0 {
+        format!("{:.1}{}", value, UNITS[unit])
+    } else {
+        format!("{:.0}{}", value, UNITS[unit])
+    }
+}
+
+pub fn format_rate(bytes_per_second: f64) -> String {
+    format!("{}/s", format_bytes(bytes_per_second))
+}
@@ -0,0 +1,59 @@
+use log::warn;
+use serde::Deserialize;
+use std::collections::HashMap;
+use zbus::{proxy, zvariant::Value, Result};
+
+#[proxy(
+    default_service = "org.freedesktop.Notifications",
+    default_path = "/org/freedesktop/Notifications",
+    interface = "org.freedesktop.Notifications"
+)]
+trait Notifications {
+    fn notify(
+        &self,
+        app_name: &str,
+        replaces_id: u32,
+        app_icon: &str,
+        summary: &str,
+        body: &str,
+        actions: &[&str],
+        hints: HashMap<&str, Value<'_>>,
+        expire_timeout: i32,
+    ) -> Result<u32>;
+}
+
+/// Urgency levels defined by the desktop notifications specification
+#[derive(Deserialize, Debug, Clone, Copy, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum Urgency {
+    Low = 0,
+    Normal = 1,
+    #[default]
+    Critical = 2,
+}
+
+async fn send_notification(summary: &str, body: &str, urgency: Urgency) -> Result<u32> {
+    let conn = zbus::Connection::session().await?;
+    let notifications = NotificationsProxy::new(&conn).await?;
+
+    notifications
+        .notify(
+            "ashell",
+            0,
+            "",
+            summary,
+            body,
+            &[],
+            HashMap::from([("urgency", Value::U8(urgency as u8))]),
+            -1,
+        )
+        .await
+}
+
+pub fn notify(summary: String, body: String, urgency: Urgency) {
+    tokio::spawn(async move {
+        if let Err(e) = send_notification(&summary, &body, urgency).await {
+            warn!("Failed to send notification {}: {:?}", summary, e);
+        }
+    });
+}
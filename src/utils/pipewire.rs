@@ -0,0 +1,878 @@
+use log::{debug, error, warn};
+use pipewire::{
+    channel,
+    context::Context,
+    core::PW_ID_CORE,
+    device::{Device as DeviceProxy, DeviceListener},
+    main_loop::MainLoop,
+    metadata::{Metadata as MetadataProxy, MetadataListener},
+    node::{Node as NodeProxy, NodeListener, NodeState},
+    registry::{GlobalObject, Registry},
+    spa::{
+        param::ParamType,
+        pod::{
+            deserialize::PodDeserializer, serialize::PodSerializer, Object, Pod, Property,
+            PropertyFlags, Value, ValueArray,
+        },
+        sys,
+        utils::{dict::DictRef, Id},
+    },
+    types::ObjectType,
+};
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap},
+    io::Cursor,
+    rc::{Rc, Weak},
+    sync::{mpsc, OnceLock},
+    thread,
+    time::Duration,
+};
+use tokio::sync::watch;
+
+/// Delay between the reconnection attempts after the daemon went away
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Media classes of the nodes tracked by the connection
+const NODE_MEDIA_CLASSES: [&str; 5] = [
+    "Audio/Sink",
+    "Audio/Source",
+    "Stream/Output/Audio",
+    "Stream/Input/Audio",
+    "Stream/Input/Video",
+];
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Node {
+    pub id: u32,
+    pub name: String,
+    pub description: String,
+    pub media_class: String,
+    pub application_name: Option<String>,
+    pub application_icon: Option<String>,
+    /// Id of the device owning the node, if any
+    pub device_id: Option<u32>,
+    /// Device index of the node inside the active card profile, used to match the routes
+    pub profile_device: Option<i32>,
+    pub running: bool,
+    /// Linear volume of each channel
+    pub volumes: Vec<f32>,
+    pub mute: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Input,
+    Output,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Route {
+    pub index: i32,
+    pub direction: Direction,
+    pub name: String,
+    pub description: String,
+    pub port_type: Option<String>,
+    pub available: bool,
+    /// Profile devices that can use this route
+    pub devices: Vec<i32>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Profile {
+    pub index: i32,
+    pub name: String,
+    pub description: String,
+    pub available: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Device {
+    pub id: u32,
+    pub description: String,
+    pub profiles: BTreeMap<i32, Profile>,
+    pub active_profile: Option<i32>,
+    pub routes: BTreeMap<i32, Route>,
+    /// Active route of each profile device
+    pub active_routes: HashMap<i32, i32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Link {
+    pub output_node: u32,
+    pub input_node: u32,
+}
+
+/// Snapshot of the PipeWire graph, published after every change
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PipewireState {
+    pub nodes: BTreeMap<u32, Node>,
+    pub devices: BTreeMap<u32, Device>,
+    pub links: BTreeMap<u32, Link>,
+    pub default_sink: Option<String>,
+    pub default_source: Option<String>,
+}
+
+impl PipewireState {
+    pub fn node_by_name(&self, name: &str) -> Option<&Node> {
+        self.nodes.values().find(|n| n.name == name)
+    }
+
+    /// Nodes linked to the given node, both upstream and downstream
+    pub fn linked_nodes(&self, id: u32) -> impl Iterator<Item = u32> + '_ {
+        self.links.values().filter_map(move |link| {
+            if link.output_node == id {
+                Some(link.input_node)
+            } else if link.input_node == id {
+                Some(link.output_node)
+            } else {
+                None
+            }
+        })
+    }
+}
+
+pub enum PipewireCommand {
+    NodeVolume(u32, Vec<f32>),
+    NodeMute(u32, bool),
+    DefaultSink(String),
+    DefaultSource(String),
+    /// Device id, profile device and route index
+    DeviceRoute(u32, i32, i32),
+    /// Device id and profile index
+    DeviceProfile(u32, i32),
+    /// Stream id and target node name
+    MoveStream(u32, String),
+}
+
+/// Connection to the PipeWire daemon, shared by all the modules
+pub struct Pipewire {
+    state: watch::Receiver<PipewireState>,
+    commands: channel::Sender<PipewireCommand>,
+}
+
+impl Pipewire {
+    pub fn subscribe(&self) -> watch::Receiver<PipewireState> {
+        self.state.clone()
+    }
+
+    pub fn state(&self) -> PipewireState {
+        self.state.borrow().clone()
+    }
+
+    pub fn send(&self, command: PipewireCommand) {
+        if self.commands.send(command).is_err() {
+            error!("PipeWire main loop is not running");
+        }
+    }
+}
+
+static PIPEWIRE: OnceLock<Option<Pipewire>> = OnceLock::new();
+
+/// Returns the shared connection, the PipeWire main loop thread is started by the first call.
+/// Once connected the thread reconnects on its own when the daemon is restarted
+pub fn connection() -> Option<&'static Pipewire> {
+    PIPEWIRE
+        .get_or_init(|| {
+            let (state_tx, state_rx) = watch::channel(PipewireState::default());
+            let (commands_tx, commands_rx) = channel::channel::<PipewireCommand>();
+            let (ready_tx, ready_rx) = mpsc::channel();
+
+            thread::spawn(move || {
+                let mut commands = Some(commands_rx);
+
+                if let Err(e) = run(&state_tx, &mut commands, &ready_tx) {
+                    error!("PipeWire connection failed: {}", e);
+                    let _ = ready_tx.send(false);
+                    return;
+                }
+
+                loop {
+                    state_tx.send_replace(PipewireState::default());
+                    thread::sleep(RECONNECT_INTERVAL);
+
+                    if let Err(e) = run(&state_tx, &mut commands, &ready_tx) {
+                        warn!("PipeWire reconnection failed: {}", e);
+                    }
+                }
+            });
+
+            if ready_rx.recv().unwrap_or_default() {
+                Some(Pipewire {
+                    state: state_rx,
+                    commands: commands_tx,
+                })
+            } else {
+                None
+            }
+        })
+        .as_ref()
+}
+
+/// Same as [`connection`] without blocking the async runtime while the first call connects
+pub async fn connect() -> Option<&'static Pipewire> {
+    tokio::task::spawn_blocking(connection).await.ok().flatten()
+}
+
+enum BoundProxy {
+    Node(NodeProxy, NodeListener),
+    Device(DeviceProxy, DeviceListener),
+    Metadata(MetadataProxy, MetadataListener),
+}
+
+struct Connection {
+    registry: Weak<Registry>,
+    state: RefCell<PipewireState>,
+    proxies: RefCell<HashMap<u32, BoundProxy>>,
+    publisher: watch::Sender<PipewireState>,
+}
+
+impl Connection {
+    /// Publishes the state only when it differs from the last published one,
+    /// most param events repeat values that are already known
+    fn publish(&self) {
+        let state = self.state.borrow();
+
+        self.publisher.send_if_modified(|published| {
+            if *published == *state {
+                false
+            } else {
+                published.clone_from(&state);
+                true
+            }
+        });
+    }
+
+    fn update(&self, f: impl FnOnce(&mut PipewireState)) {
+        f(&mut self.state.borrow_mut());
+        self.publish();
+    }
+
+    fn add_global(self: &Rc<Self>, global: &GlobalObject<&DictRef>) {
+        let Some(registry) = self.registry.upgrade() else {
+            return;
+        };
+        let props = global.props;
+        let get = |key: &str| props.and_then(|p| p.get(key)).map(str::to_string);
+
+        match global.type_ {
+            ObjectType::Node => {
+                let Some(media_class) =
+                    get("media.class").filter(|class| NODE_MEDIA_CLASSES.contains(&class.as_str()))
+                else {
+                    return;
+                };
+
+                let proxy = match registry.bind::<NodeProxy, _>(global) {
+                    Ok(proxy) => proxy,
+                    Err(e) => {
+                        warn!("Failed to bind node {}: {}", global.id, e);
+                        return;
+                    }
+                };
+
+                let id = global.id;
+                let listener = proxy
+                    .add_listener_local()
+                    .info({
+                        let connection = Rc::downgrade(self);
+                        move |info| {
+                            if let Some(connection) = connection.upgrade() {
+                                connection.update(|state| {
+                                    if let Some(node) = state.nodes.get_mut(&id) {
+                                        node.running = matches!(info.state(), NodeState::Running);
+                                        if let Some(props) = info.props() {
+                                            update_node_props(node, props);
+                                        }
+                                    }
+                                });
+                            }
+                        }
+                    })
+                    .param({
+                        let connection = Rc::downgrade(self);
+                        move |_, param_type, _, _, pod| {
+                            if let (Some(connection), Some(object)) =
+                                (connection.upgrade(), pod.and_then(parse_object))
+                            {
+                                if param_type == ParamType::Props {
+                                    connection.update(|state| {
+                                        if let Some(node) = state.nodes.get_mut(&id) {
+                                            update_node_volume(node, &object);
+                                        }
+                                    });
+                                }
+                            }
+                        }
+                    })
+                    .register();
+                proxy.subscribe_params(&[ParamType::Props]);
+
+                let mut node = Node {
+                    id,
+                    media_class,
+                    ..Default::default()
+                };
+                if let Some(props) = props {
+                    update_node_props(&mut node, props);
+                }
+
+                debug!("New PipeWire node: {:?}", node);
+                self.update(|state| {
+                    state.nodes.insert(id, node);
+                });
+                self.proxies
+                    .borrow_mut()
+                    .insert(id, BoundProxy::Node(proxy, listener));
+            }
+            ObjectType::Device => {
+                if get("media.class").as_deref() != Some("Audio/Device") {
+                    return;
+                }
+
+                let proxy = match registry.bind::<DeviceProxy, _>(global) {
+                    Ok(proxy) => proxy,
+                    Err(e) => {
+                        warn!("Failed to bind device {}: {}", global.id, e);
+                        return;
+                    }
+                };
+
+                let id = global.id;
+                let listener = proxy
+                    .add_listener_local()
+                    .info({
+                        let connection = Rc::downgrade(self);
+                        move |info| {
+                            if let (Some(connection), Some(props)) =
+                                (connection.upgrade(), info.props())
+                            {
+                                connection.update(|state| {
+                                    if let Some(device) = state.devices.get_mut(&id) {
+                                        if let Some(description) = props
+                                            .get("device.description")
+                                            .or_else(|| props.get("device.name"))
+                                        {
+                                            device.description = description.to_string();
+                                        }
+                                    }
+                                });
+                            }
+                        }
+                    })
+                    .param({
+                        let connection = Rc::downgrade(self);
+                        move |_, param_type, _, _, pod| {
+                            if let (Some(connection), Some(object)) =
+                                (connection.upgrade(), pod.and_then(parse_object))
+                            {
+                                connection.update(|state| {
+                                    if let Some(device) = state.devices.get_mut(&id) {
+                                        update_device_param(device, param_type, &object);
+                                    }
+                                });
+                            }
+                        }
+                    })
+                    .register();
+                proxy.subscribe_params(&[
+                    ParamType::EnumProfile,
+                    ParamType::Profile,
+                    ParamType::EnumRoute,
+                    ParamType::Route,
+                ]);
+
+                self.update(|state| {
+                    state.devices.insert(
+                        id,
+                        Device {
+                            id,
+                            description: get("device.description")
+                                .or_else(|| get("device.name"))
+                                .unwrap_or_default(),
+                            ..Default::default()
+                        },
+                    );
+                });
+                self.proxies
+                    .borrow_mut()
+                    .insert(id, BoundProxy::Device(proxy, listener));
+            }
+            ObjectType::Link => {
+                let output_node = get("link.output.node").and_then(|id| id.parse().ok());
+                let input_node = get("link.input.node").and_then(|id| id.parse().ok());
+
+                if let (Some(output_node), Some(input_node)) = (output_node, input_node) {
+                    self.update(|state| {
+                        state.links.insert(
+                            global.id,
+                            Link {
+                                output_node,
+                                input_node,
+                            },
+                        );
+                    });
+                }
+            }
+            ObjectType::Metadata => {
+                // WirePlumber stores the default devices in the "default" metadata
+                if get("metadata.name").as_deref() != Some("default") {
+                    return;
+                }
+
+                let proxy = match registry.bind::<MetadataProxy, _>(global) {
+                    Ok(proxy) => proxy,
+                    Err(e) => {
+                        warn!("Failed to bind metadata {}: {}", global.id, e);
+                        return;
+                    }
+                };
+
+                let listener = proxy
+                    .add_listener_local()
+                    .property({
+                        let connection = Rc::downgrade(self);
+                        move |subject, key, _, value| {
+                            if let Some(connection) = connection.upgrade() {
+                                if subject == 0 {
+                                    let name = value.and_then(parse_metadata_name);
+                                    match key {
+                                        Some("default.audio.sink") => {
+                                            connection.update(|state| state.default_sink = name)
+                                        }
+                                        Some("default.audio.source") => {
+                                            connection.update(|state| state.default_source = name)
+                                        }
+                                        _ => {}
+                                    }
+                                }
+                            }
+
+                            0
+                        }
+                    })
+                    .register();
+
+                self.proxies
+                    .borrow_mut()
+                    .insert(global.id, BoundProxy::Metadata(proxy, listener));
+            }
+            _ => {}
+        }
+    }
+
+    fn remove_global(&self, id: u32) {
+        self.proxies.borrow_mut().remove(&id);
+        self.update(|state| {
+            state.nodes.remove(&id);
+            state.devices.remove(&id);
+            state.links.remove(&id);
+        });
+    }
+
+    fn with_metadata(&self, f: impl FnOnce(&MetadataProxy)) -> bool {
+        let proxies = self.proxies.borrow();
+        let metadata = proxies.values().find_map(|proxy| match proxy {
+            BoundProxy::Metadata(metadata, _) => Some(metadata),
+            _ => None,
+        });
+
+        if let Some(metadata) = metadata {
+            f(metadata);
+        }
+
+        metadata.is_some()
+    }
+
+    fn handle_command(&self, command: PipewireCommand) {
+        let proxies = self.proxies.borrow();
+
+        match command {
+            PipewireCommand::NodeVolume(id, volumes) => {
+                drop(proxies);
+                self.set_node_props(
+                    id,
+                    vec![property(
+                        sys::SPA_PROP_channelVolumes,
+                        Value::ValueArray(ValueArray::Float(volumes)),
+                    )],
+                );
+            }
+            PipewireCommand::NodeMute(id, mute) => {
+                drop(proxies);
+                self.set_node_props(id, vec![property(sys::SPA_PROP_mute, Value::Bool(mute))]);
+            }
+            PipewireCommand::DefaultSink(name) => {
+                drop(proxies);
+                self.set_default("default.configured.audio.sink", &name);
+            }
+            PipewireCommand::DefaultSource(name) => {
+                drop(proxies);
+                self.set_default("default.configured.audio.source", &name);
+            }
+            PipewireCommand::DeviceRoute(id, profile_device, route) => {
+                if let Some(BoundProxy::Device(device, _)) = proxies.get(&id) {
+                    set_param(
+                        |pod| device.set_param(ParamType::Route, 0, pod),
+                        sys::SPA_TYPE_OBJECT_ParamRoute,
+                        sys::SPA_PARAM_Route,
+                        vec![
+                            property(sys::SPA_PARAM_ROUTE_index, Value::Int(route)),
+                            property(sys::SPA_PARAM_ROUTE_device, Value::Int(profile_device)),
+                            property(sys::SPA_PARAM_ROUTE_save, Value::Bool(true)),
+                        ],
+                    );
+                }
+            }
+            PipewireCommand::DeviceProfile(id, profile) => {
+                if let Some(BoundProxy::Device(device, _)) = proxies.get(&id) {
+                    set_param(
+                        |pod| device.set_param(ParamType::Profile, 0, pod),
+                        sys::SPA_TYPE_OBJECT_ParamProfile,
+                        sys::SPA_PARAM_Profile,
+                        vec![
+                            property(sys::SPA_PARAM_PROFILE_index, Value::Int(profile)),
+                            property(sys::SPA_PARAM_PROFILE_save, Value::Bool(true)),
+                        ],
+                    );
+                }
+            }
+            PipewireCommand::MoveStream(id, target) => {
+                drop(proxies);
+                let target_id = self.state.borrow().node_by_name(&target).map(|n| n.id);
+
+                self.with_metadata(|metadata| {
+                    metadata.set_property(id, "target.object", Some("Spa:String"), Some(&target));
+                    // WirePlumber versions before 0.5 only know about target.node
+                    if let Some(target_id) = target_id {
+                        metadata.set_property(
+                            id,
+                            "target.node",
+                            Some("Spa:Id"),
+                            Some(&target_id.to_string()),
+                        );
+                    }
+                });
+            }
+        }
+    }
+
+    /// Device id, profile device and active route index of a device node
+    fn active_route(&self, id: u32) -> Option<(u32, i32, i32)> {
+        let state = self.state.borrow();
+        let node = state.nodes.get(&id)?;
+        let device_id = node.device_id?;
+        let profile_device = node.profile_device?;
+        let route = state
+            .devices
+            .get(&device_id)?
+            .active_routes
+            .get(&profile_device)?;
+
+        Some((device_id, profile_device, *route))
+    }
+
+    /// Volume and mute of device nodes belong to the active route, the same way pipewire-pulse
+    /// sets them, otherwise the session manager restores the route values.
+    /// Streams and virtual nodes don't have a route and are set on the node itself
+    fn set_node_props(&self, id: u32, props: Vec<Property>) {
+        let proxies = self.proxies.borrow();
+        let route = self
+            .active_route(id)
+            .and_then(|(device_id, profile_device, route)| {
+                Some((proxies.get(&device_id)?, profile_device, route))
+            });
+
+        match (route, proxies.get(&id)) {
+            (Some((BoundProxy::Device(device, _), profile_device, route)), _) => {
+                set_param(
+                    |pod| device.set_param(ParamType::Route, 0, pod),
+                    sys::SPA_TYPE_OBJECT_ParamRoute,
+                    sys::SPA_PARAM_Route,
+                    vec![
+                        property(sys::SPA_PARAM_ROUTE_index, Value::Int(route)),
+                        property(sys::SPA_PARAM_ROUTE_device, Value::Int(profile_device)),
+                        property(
+                            sys::SPA_PARAM_ROUTE_props,
+                            Value::Object(Object {
+                                type_: sys::SPA_TYPE_OBJECT_Props,
+                                id: sys::SPA_PARAM_Route,
+                                properties: props,
+                            }),
+                        ),
+                        property(sys::SPA_PARAM_ROUTE_save, Value::Bool(true)),
+                    ],
+                );
+            }
+            (_, Some(BoundProxy::Node(node, _))) => {
+                set_param(
+                    |pod| node.set_param(ParamType::Props, 0, pod),
+                    sys::SPA_TYPE_OBJECT_Props,
+                    sys::SPA_PARAM_Props,
+                    props,
+                );
+            }
+            _ => {}
+        }
+    }
+
+    fn set_default(&self, key: &str, name: &str) {
+        let value = serde_json::json!({ "name": name }).to_string();
+
+        if !self.with_metadata(|metadata| {
+            metadata.set_property(0, key, Some("Spa:String:JSON"), Some(&value));
+        }) {
+            warn!("Default metadata not available, can't set {}", key);
+        }
+    }
+}
+
+/// Runs the main loop until the connection to the daemon is lost,
+/// the commands receiver is given back to be attached to the next connection
+fn run(
+    publisher: &watch::Sender<PipewireState>,
+    commands: &mut Option<channel::Receiver<PipewireCommand>>,
+    ready: &mpsc::Sender<bool>,
+) -> Result<(), pipewire::Error> {
+    let mainloop = MainLoop::new(None)?;
+    let context = Context::new(&mainloop)?;
+    let core = context.connect(None)?;
+    let registry = Rc::new(core.get_registry()?);
+
+    // the core reports an error when the daemon goes away, e.g. on restart
+    let _core_listener = core
+        .add_listener_local()
+        .error({
+            let mainloop = mainloop.downgrade();
+            move |id, _, res, message| {
+                error!("PipeWire error on {}: {} {}", id, res, message);
+
+                if id == PW_ID_CORE {
+                    if let Some(mainloop) = mainloop.upgrade() {
+                        mainloop.quit();
+                    }
+                }
+            }
+        })
+        .register();
+
+    let connection = Rc::new(Connection {
+        registry: Rc::downgrade(&registry),
+        state: RefCell::new(PipewireState::default()),
+        proxies: RefCell::new(HashMap::new()),
+        publisher: publisher.clone(),
+    });
+
+    let _listener = registry
+        .add_listener_local()
+        .global({
+            let connection = connection.clone();
+            move |global| connection.add_global(global)
+        })
+        .global_remove({
+            let connection = connection.clone();
+            move |id| connection.remove_global(id)
+        })
+        .register();
+
+    let Some(receiver) = commands.take() else {
+        return Ok(());
+    };
+    let attached = receiver.attach(mainloop.loop_(), {
+        let connection = connection.clone();
+        move |command| connection.handle_command(command)
+    });
+
+    let _ = ready.send(true);
+    mainloop.run();
+
+    warn!("PipeWire mainloop exited");
+    *commands = Some(attached.deattach());
+
+    Ok(())
+}
+
+fn update_node_props(node: &mut Node, props: &DictRef) {
+    if let Some(name) = props.get("node.name") {
+        node.name = name.to_string();
+    }
+    if let Some(description) = props
+        .get("node.description")
+        .or_else(|| props.get("node.nick"))
+    {
+        node.description = description.to_string();
+    }
+    if let Some(application_name) = props.get("application.name") {
+        node.application_name = Some(application_name.to_string());
+    }
+    if let Some(icon) = props
+        .get("application.icon-name")
+        .or_else(|| props.get("application.process.binary"))
+    {
+        node.application_icon = Some(icon.to_string());
+    }
+    if let Some(device_id) = props.get("device.id").and_then(|id| id.parse().ok()) {
+        node.device_id = Some(device_id);
+    }
+    if let Some(profile_device) = props
+        .get("card.profile.device")
+        .and_then(|device| device.parse().ok())
+    {
+        node.profile_device = Some(profile_device);
+    }
+}
+
+fn update_node_volume(node: &mut Node, props: &Object) {
+    for property in props.properties.iter() {
+        match (property.key, &property.value) {
+            (sys::SPA_PROP_channelVolumes, Value::ValueArray(ValueArray::Float(volumes))) => {
+                node.volumes.clone_from(volumes);
+            }
+            (sys::SPA_PROP_mute, Value::Bool(mute)) => node.mute = *mute,
+            _ => {}
+        }
+    }
+}
+
+fn update_device_param(device: &mut Device, param_type: ParamType, object: &Object) {
+    match param_type {
+        ParamType::EnumProfile => {
+            let profile = Profile {
+                index: int_value(object, sys::SPA_PARAM_PROFILE_index).unwrap_or_default(),
+                name: string_value(object, sys::SPA_PARAM_PROFILE_name).unwrap_or_default(),
+                description: string_value(object, sys::SPA_PARAM_PROFILE_description)
+                    .unwrap_or_default(),
+                available: id_value(object, sys::SPA_PARAM_PROFILE_available)
+                    != Some(sys::SPA_PARAM_AVAILABILITY_no),
+            };
+            device.profiles.insert(profile.index, profile);
+        }
+        ParamType::Profile => {
+            device.active_profile = int_value(object, sys::SPA_PARAM_PROFILE_index);
+        }
+        ParamType::EnumRoute => {
+            let route = Route {
+                index: int_value(object, sys::SPA_PARAM_ROUTE_index).unwrap_or_default(),
+                direction: if id_value(object, sys::SPA_PARAM_ROUTE_direction)
+                    == Some(sys::SPA_DIRECTION_INPUT)
+                {
+                    Direction::Input
+                } else {
+                    Direction::Output
+                },
+                name: string_value(object, sys::SPA_PARAM_ROUTE_name).unwrap_or_default(),
+                description: string_value(object, sys::SPA_PARAM_ROUTE_description)
+                    .unwrap_or_default(),
+                port_type: route_info(object, "port.type"),
+                available: id_value(object, sys::SPA_PARAM_ROUTE_available)
+                    != Some(sys::SPA_PARAM_AVAILABILITY_no),
+                devices: object
+                    .properties
+                    .iter()
+                    .find(|p| p.key == sys::SPA_PARAM_ROUTE_devices)
+                    .and_then(|p| match &p.value {
+                        Value::ValueArray(ValueArray::Int(devices)) => Some(devices.clone()),
+                        _ => None,
+                    })
+                    .unwrap_or_default(),
+            };
+            device.routes.insert(route.index, route);
+        }
+        ParamType::Route => {
+            if let (Some(profile_device), Some(route)) = (
+                int_value(object, sys::SPA_PARAM_ROUTE_device),
+                int_value(object, sys::SPA_PARAM_ROUTE_index),
+            ) {
+                device.active_routes.insert(profile_device, route);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn parse_object(pod: &Pod) -> Option<Object> {
+    match PodDeserializer::deserialize_any_from(pod.as_bytes()) {
+        Ok((_, Value::Object(object))) => Some(object),
+        _ => None,
+    }
+}
+
+/// Node name stored in a metadata JSON value, e.g. `{ "name": "alsa_output.pci" }`
+fn parse_metadata_name(value: &str) -> Option<String> {
+    serde_json::from_str::<serde_json::Value>(value)
+        .ok()?
+        .get("name")?
+        .as_str()
+        .map(str::to_string)
+}
+
+fn find_value(object: &Object, key: u32) -> Option<&Value> {
+    object
+        .properties
+        .iter()
+        .find(|p| p.key == key)
+        .map(|p| &p.value)
+}
+
+fn int_value(object: &Object, key: u32) -> Option<i32> {
+    match find_value(object, key)? {
+        Value::Int(value) => Some(*value),
+        _ => None,
+    }
+}
+
+fn id_value(object: &Object, key: u32) -> Option<u32> {
+    match find_value(object, key)? {
+        Value::Id(Id(value)) => Some(*value),
+        _ => None,
+    }
+}
+
+fn string_value(object: &Object, key: u32) -> Option<String> {
+    match find_value(object, key)? {
+        Value::String(value) => Some(value.clone()),
+        _ => None,
+    }
+}
+
+/// Route info is a struct with the number of items followed by the key value pairs
+fn route_info(object: &Object, key: &str) -> Option<String> {
+    let Value::Struct(info) = find_value(object, sys::SPA_PARAM_ROUTE_info)? else {
+        return None;
+    };
+
+    info.iter()
+        .skip(1)
+        .collect::<Vec<_>>()
+        .chunks(2)
+        .find_map(|pair| match pair {
+            [Value::String(k), Value::String(v)] if k == key => Some(v.clone()),
+            _ => None,
+        })
+}
+
+fn property(key: u32, value: Value) -> Property {
+    Property {
+        key,
+        flags: PropertyFlags::empty(),
+        value,
+    }
+}
+
+fn set_param(apply: impl FnOnce(&Pod), type_: u32, id: u32, properties: Vec<Property>) {
+    let value = Value::Object(Object {
+        type_,
+        id,
+        properties,
+    });
+
+    match PodSerializer::serialize(Cursor::new(Vec::new()), &value) {
+        Ok((cursor, _)) => {
+            let bytes = cursor.into_inner();
+            if let Some(pod) = Pod::from_bytes(&bytes) {
+                apply(pod);
+            }
+        }
+        Err(e) => error!("Failed to serialize the PipeWire param: {:?}", e),
+    }
+}
@@ -1,109 +1,74 @@
 use itertools::Itertools;
-use std::thread;
 
 use iced::{futures::SinkExt, Subscription};
-use log::{debug, warn};
-use pipewire::{context::Context, main_loop::MainLoop};
+use log::warn;
 
-use crate::modules::privacy::{ApplicationNode, Media, PrivacyMessage};
+use crate::{
+    modules::privacy::{ApplicationNode, Media, PrivacyMessage},
+    utils::{audio::SOURCE_METER_NAME, pipewire::PipewireState},
+};
 
-pub fn subscription() -> Subscription<PrivacyMessage> {
-    iced::subscription::channel(
-        "privacy-dbus-connection-listener",
-        100,
-        |mut output| async move {
-            enum PipewireEvent {
-                AddNode(u32, Media, String),
-                RemoveNode(u32),
+fn applications(state: &PipewireState) -> Vec<ApplicationNode> {
+    state
+        .nodes
+        .values()
+        .filter_map(|node| {
+            if node.application_name.as_deref() == Some(SOURCE_METER_NAME) {
+                return None;
             }
 
-            let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<PipewireEvent>();
-
-            thread::spawn(move || {
-                let mainloop = MainLoop::new(None).unwrap();
-                let context = Context::new(&mainloop).unwrap();
-                let core = context.connect(None).unwrap();
-                let registry = core.get_registry().unwrap();
+            let media = match node.media_class.as_str() {
+                "Stream/Input/Video" => Media::Video,
+                "Stream/Input/Audio" => Media::Audio,
+                _ => return None,
+            };
 
-                let _listener = registry
-                    .add_listener_local()
-                    .global({
-                        let tx = tx.clone();
-                        move |global| {
-                            if let Some(props) = global.props {
-                                if let Some(media) = props.get("media.class").filter(|v| {
-                                    v == &"Stream/Input/Video" || v == &"Stream/Input/Audio"
-                                }) {
-                                    debug!("New global: {:?}", global);
-                                    tx.send(PipewireEvent::AddNode(
-                                        global.id,
-                                        if media == "Stream/Input/Video" {
-                                            Media::Video
-                                        } else {
-                                            Media::Audio
-                                        },
-                                        props.get("node.name").map_or_else(
-                                            || "generic".to_string(),
-                                            |name| name.to_lowercase(),
-                                        ),
-                                    ))
-                                    .unwrap();
-                                }
-                            }
-                        }
-                    })
-                    .global_remove({
-                        let tx = tx.clone();
-                        move |id| {
-                            debug!("Remove global: {}", id);
-                            tx.send(PipewireEvent::RemoveNode(id)).unwrap();
-                        }
-                    })
-                    .register();
-
-                mainloop.run();
+            Some(ApplicationNode {
+                id: node.id,
+                media,
+                application: if node.name.is_empty() {
+                    "generic".to_string()
+                } else {
+                    node.name.to_lowercase()
+                },
+            })
+        })
+        .unique_by(|a| a.application.clone())
+        .collect()
+}
 
-                warn!("Pipewire mainloop exited");
-            });
+pub fn subscription() -> Subscription<PrivacyMessage> {
+    iced::subscription::channel("privacy-pipewire-listener", 100, |mut output| async move {
+        if let Some(pipewire) = crate::utils::pipewire::connect().await {
+            let mut state_rx = pipewire.subscribe();
+            let mut current: Option<Vec<(u32, String)>> = None;
 
-            let mut applications = vec![];
             loop {
-                match rx.recv().await {
-                    Some(PipewireEvent::AddNode(id, media, application)) => {
-                        applications.push(ApplicationNode {
-                            id,
-                            media,
-                            application,
-                        });
+                let applications = applications(&state_rx.borrow_and_update());
 
-                        output
-                            .send(PrivacyMessage::Applications(
-                                applications
-                                    .iter()
-                                    .unique_by(|a| &a.application)
-                                    .cloned()
-                                    .collect::<Vec<_>>(),
-                            ))
-                            .await
-                            .unwrap();
-                    }
-                    Some(PipewireEvent::RemoveNode(id)) => {
-                        applications.retain(|n| n.id != id);
+                // the state changes often, e.g. on volume updates
+                let ids = applications
+                    .iter()
+                    .map(|a| (a.id, a.application.clone()))
+                    .collect::<Vec<_>>();
+                if current.as_ref() != Some(&ids) {
+                    current = Some(ids);
+                    let _ = output
+                        .send(PrivacyMessage::Applications(applications))
+                        .await;
+                }
 
-                        output
-                            .send(PrivacyMessage::Applications(
-                                applications
-                                    .iter()
-                                    .unique_by(|a| &a.application)
-                                    .cloned()
-                                    .collect::<Vec<_>>(),
-                            ))
-                            .await
-                            .unwrap();
-                    }
-                    _ => {}
+                if state_rx.changed().await.is_err() {
+                    warn!("PipeWire connection closed");
+                    break;
                 }
             }
-        },
-    )
+        } else {
+            warn!("PipeWire is not available, privacy indicators are disabled");
+        }
+
+        loop {
+            iced::futures::future::pending::<()>().await;
+        }
+    })
 }
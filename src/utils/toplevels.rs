@@ -0,0 +1,371 @@
+use crate::modules::taskbar::{TaskbarMessage, Toplevel};
+use hyprland::{
+    data::{Client, Clients},
+    dispatch::{Dispatch, DispatchType},
+    event_listener::EventListener,
+    shared::{HyprData, HyprDataActiveOptional, HyprDataVec},
+};
+use iced::{
+    futures::{FutureExt, SinkExt},
+    Subscription,
+};
+use log::{debug, error, warn};
+use std::{cell::RefCell, collections::HashMap, error::Error, thread};
+use wayland_client::{
+    event_created_child,
+    protocol::{
+        wl_registry::{self, WlRegistry},
+        wl_seat::WlSeat,
+    },
+    Connection, Dispatch as WlDispatch, EventQueue, Proxy, QueueHandle,
+};
+use wayland_protocols_wlr::foreign_toplevel::v1::client::{
+    zwlr_foreign_toplevel_handle_v1::{self, ZwlrForeignToplevelHandleV1},
+    zwlr_foreign_toplevel_manager_v1::{self, ZwlrForeignToplevelManagerV1},
+};
+
+pub enum ToplevelCommand {
+    Activate(String),
+    Close(String),
+}
+
+type Handles = HashMap<String, ZwlrForeignToplevelHandleV1>;
+
+/// Toplevels snapshot sent on every change, together with the handles used to act on them
+type ToplevelsUpdate = (Vec<Toplevel>, Handles);
+
+/// Toplevels state tracked through the wlr foreign toplevel protocol
+struct ToplevelsData {
+    seat: Option<WlSeat>,
+    manager: Option<ZwlrForeignToplevelManagerV1>,
+    toplevels: HashMap<String, Toplevel>,
+    handles: Handles,
+    tx: tokio::sync::mpsc::UnboundedSender<ToplevelsUpdate>,
+}
+
+impl ToplevelsData {
+    fn send(&self) {
+        let mut toplevels = self.toplevels.values().cloned().collect::<Vec<_>>();
+        toplevels.sort_by(|a, b| a.id.cmp(&b.id));
+
+        let _ = self.tx.send((toplevels, self.handles.clone()));
+    }
+}
+
+impl WlDispatch<WlRegistry, ()> for ToplevelsData {
+    fn event(
+        state: &mut Self,
+        proxy: &WlRegistry,
+        event: wl_registry::Event,
+        _data: &(),
+        _conn: &Connection,
+        qhandle: &QueueHandle<Self>,
+    ) {
+        if let wl_registry::Event::Global {
+            name,
+            interface,
+            version,
+        } = event
+        {
+            if interface == WlSeat::interface().name && state.seat.is_none() {
+                debug!("Adding seat with name {name} and version {version}");
+                state.seat = Some(proxy.bind(name, version.min(7), qhandle, ()));
+            } else if interface == ZwlrForeignToplevelManagerV1::interface().name
+                && state.manager.is_none()
+            {
+                debug!("Adding foreign toplevel manager with name {name} and version {version}");
+                state.manager = Some(proxy.bind(name, version.min(3), qhandle, ()));
+            }
+        }
+    }
+}
+
+impl WlDispatch<WlSeat, ()> for ToplevelsData {
+    fn event(
+        _state: &mut Self,
+        _proxy: &WlSeat,
+        _event: <WlSeat as Proxy>::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qhandle: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+impl WlDispatch<ZwlrForeignToplevelManagerV1, ()> for ToplevelsData {
+    fn event(
+        state: &mut Self,
+        _proxy: &ZwlrForeignToplevelManagerV1,
+        event: zwlr_foreign_toplevel_manager_v1::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qhandle: &QueueHandle<Self>,
+    ) {
+        match event {
+            zwlr_foreign_toplevel_manager_v1::Event::Toplevel { toplevel } => {
+                let id = toplevel.id().protocol_id().to_string();
+                state.toplevels.insert(
+                    id.clone(),
+                    Toplevel {
+                        id: id.clone(),
+                        ..Default::default()
+                    },
+                );
+                state.handles.insert(id, toplevel);
+            }
+            zwlr_foreign_toplevel_manager_v1::Event::Finished => {
+                warn!("Foreign toplevel manager finished");
+                state.manager = None;
+            }
+            _ => {}
+        }
+    }
+
+    event_created_child!(ToplevelsData, ZwlrForeignToplevelManagerV1, [
+        zwlr_foreign_toplevel_manager_v1::EVT_TOPLEVEL_OPCODE => (ZwlrForeignToplevelHandleV1, ())
+    ]);
+}
+
+impl WlDispatch<ZwlrForeignToplevelHandleV1, ()> for ToplevelsData {
+    fn event(
+        state: &mut Self,
+        proxy: &ZwlrForeignToplevelHandleV1,
+        event: zwlr_foreign_toplevel_handle_v1::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qhandle: &QueueHandle<Self>,
+    ) {
+        let id = proxy.id().protocol_id().to_string();
+        match event {
+            zwlr_foreign_toplevel_handle_v1::Event::Title { title } => {
+                if let Some(toplevel) = state.toplevels.get_mut(&id) {
+                    toplevel.title = title;
+                }
+            }
+            zwlr_foreign_toplevel_handle_v1::Event::AppId { app_id } => {
+                if let Some(toplevel) = state.toplevels.get_mut(&id) {
+                    toplevel.app_id = app_id;
+                }
+            }
+            zwlr_foreign_toplevel_handle_v1::Event::State { state: value } => {
+                if let Some(toplevel) = state.toplevels.get_mut(&id) {
+                    toplevel.active = value
+                        .chunks_exact(4)
+                        .map(|c| u32::from_ne_bytes([c[0], c[1], c[2], c[3]]))
+                        .any(|v| v == zwlr_foreign_toplevel_handle_v1::State::Activated as u32);
+                }
+            }
+            zwlr_foreign_toplevel_handle_v1::Event::Done => {
+                state.send();
+            }
+            zwlr_foreign_toplevel_handle_v1::Event::Closed => {
+                state.toplevels.remove(&id);
+                state.handles.remove(&id);
+                proxy.destroy();
+                state.send();
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Wayland connection bound to the wlr foreign toplevel manager
+struct WlrToplevels {
+    connection: Connection,
+    event_queue: EventQueue<ToplevelsData>,
+    data: ToplevelsData,
+}
+
+impl WlrToplevels {
+    fn connect(
+        tx: tokio::sync::mpsc::UnboundedSender<ToplevelsUpdate>,
+    ) -> Result<Self, Box<dyn Error>> {
+        let connection = Connection::connect_to_env()?;
+        let mut event_queue = connection.new_event_queue();
+        let qhandle = event_queue.handle();
+        connection.display().get_registry(&qhandle, ());
+
+        let mut data = ToplevelsData {
+            seat: None,
+            manager: None,
+            toplevels: HashMap::new(),
+            handles: HashMap::new(),
+            tx,
+        };
+        event_queue.roundtrip(&mut data)?;
+
+        if data.manager.is_none() {
+            return Err("zwlr_foreign_toplevel_manager_v1 is not available".into());
+        }
+
+        Ok(Self {
+            connection,
+            event_queue,
+            data,
+        })
+    }
+}
+
+fn get_hyprland_toplevels() -> Vec<Toplevel> {
+    let active = Client::get_active()
+        .ok()
+        .flatten()
+        .map(|c| c.address.to_string());
+
+    Clients::get()
+        .map(|c| c.to_vec())
+        .unwrap_or_default()
+        .into_iter()
+        .map(|c| {
+            let id = c.address.to_string();
+            Toplevel {
+                active: active.as_ref() == Some(&id),
+                id,
+                app_id: c.class,
+                title: c.title,
+            }
+        })
+        .collect()
+}
+
+fn hyprland_dispatch(dispatcher: &str, address: &str) {
+    if let Err(e) = Dispatch::call(DispatchType::Custom(
+        dispatcher,
+        &format!("address:{}", address),
+    )) {
+        error!("failed to dispatch {} on {}: {:?}", dispatcher, address, e);
+    }
+}
+
+async fn hyprland_listener(
+    output: iced::futures::channel::mpsc::Sender<TaskbarMessage>,
+    mut rx: tokio::sync::mpsc::UnboundedReceiver<ToplevelCommand>,
+) {
+    tokio::spawn(async move {
+        while let Some(command) = rx.recv().await {
+            match command {
+                ToplevelCommand::Activate(address) => hyprland_dispatch("focuswindow", &address),
+                ToplevelCommand::Close(address) => hyprland_dispatch("closewindow", &address),
+            }
+        }
+    });
+
+    let output = RefCell::new(output);
+    let _ = output
+        .borrow_mut()
+        .try_send(TaskbarMessage::Toplevels(get_hyprland_toplevels()));
+
+    let mut event_listener = EventListener::new();
+
+    event_listener.add_window_open_handler({
+        let output = output.clone();
+        move |_| {
+            let _ = output
+                .borrow_mut()
+                .try_send(TaskbarMessage::Toplevels(get_hyprland_toplevels()));
+        }
+    });
+
+    event_listener.add_window_close_handler({
+        let output = output.clone();
+        move |_| {
+            let _ = output
+                .borrow_mut()
+                .try_send(TaskbarMessage::Toplevels(get_hyprland_toplevels()));
+        }
+    });
+
+    event_listener.add_window_title_change_handler({
+        let output = output.clone();
+        move |_| {
+            let _ = output
+                .borrow_mut()
+                .try_send(TaskbarMessage::Toplevels(get_hyprland_toplevels()));
+        }
+    });
+
+    event_listener.add_active_window_change_handler({
+        let output = output.clone();
+        move |_| {
+            let _ = output
+                .borrow_mut()
+                .try_send(TaskbarMessage::Toplevels(get_hyprland_toplevels()));
+        }
+    });
+
+    event_listener
+        .start_listener_async()
+        .await
+        .expect("failed to start toplevels listener");
+}
+
+pub fn subscription(
+    rx: Option<tokio::sync::mpsc::UnboundedReceiver<ToplevelCommand>>,
+) -> Subscription<TaskbarMessage> {
+    iced::subscription::channel("toplevels-listener", 100, |mut output| async move {
+        let mut rx = rx.expect("Failed to get commander receiver");
+        let (tx, mut toplevels_rx) = tokio::sync::mpsc::unbounded_channel::<ToplevelsUpdate>();
+
+        match WlrToplevels::connect(tx) {
+            Ok(WlrToplevels {
+                connection,
+                mut event_queue,
+                mut data,
+            }) => {
+                let seat = data.seat.clone();
+                let mut handles = Handles::new();
+
+                thread::spawn(move || loop {
+                    if let Err(e) = event_queue.blocking_dispatch(&mut data) {
+                        error!("Foreign toplevel event queue error: {:?}", e);
+                        break;
+                    }
+                });
+
+                loop {
+                    iced::futures::select! {
+                        update = toplevels_rx.recv().fuse() => {
+                            // the event queue thread is gone, the sender has been dropped with it
+                            let Some((toplevels, new_handles)) = update else {
+                                break;
+                            };
+                            handles = new_handles;
+                            let _ = output.send(TaskbarMessage::Toplevels(toplevels)).await;
+                        },
+                        command = rx.recv().fuse() => {
+                            match command {
+                                Some(ToplevelCommand::Activate(id)) => {
+                                    if let (Some(handle), Some(seat)) = (handles.get(&id), &seat) {
+                                        handle.activate(seat);
+                                    }
+                                }
+                                Some(ToplevelCommand::Close(id)) => {
+                                    if let Some(handle) = handles.get(&id) {
+                                        handle.close();
+                                    }
+                                }
+                                None => {
+                                    // no more commands, keep forwarding the toplevels only
+                                    while let Some((toplevels, _)) = toplevels_rx.recv().await {
+                                        let _ = output.send(TaskbarMessage::Toplevels(toplevels)).await;
+                                    }
+                                    break;
+                                }
+                            }
+                            let _ = connection.flush();
+                        }
+                    }
+                }
+
+                warn!("Foreign toplevel listener exited, falling back to hyprland clients");
+            }
+            Err(e) => {
+                warn!("Falling back to hyprland clients: {}", e);
+            }
+        }
+
+        hyprland_listener(output, rx).await;
+
+        panic!("Exiting hyprland event listener");
+    })
+}